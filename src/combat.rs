@@ -0,0 +1,160 @@
+use crate::{
+    Attributes, DefenseBonus, EquipmentSlot, Equipped, InBackpack, Item, NaturalAttackDefense,
+    Stackable,
+};
+use rltk::RandomNumberGenerator;
+use specs::prelude::*;
+use specs::storage::MaskedStorage;
+use std::ops::Deref;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum AttackResult {
+    Fumble,
+    Miss,
+    Hit,
+    Critical,
+}
+
+//10 (or natural armour) + quickness + any worn defense bonuses
+pub fn armor_class(
+    target: Entity,
+    natural_attacks: &ReadStorage<NaturalAttackDefense>,
+    attributes: &ReadStorage<Attributes>,
+    defense_bonuses: &ReadStorage<DefenseBonus>,
+    equipped: &ReadStorage<Equipped>,
+) -> i32 {
+    let mut armor_class = natural_attacks
+        .get(target)
+        .and_then(|n| n.armor_class)
+        .unwrap_or(10);
+    armor_class += attributes.get(target).map_or(0, |a| a.quickness.modifier());
+    for (defense_bonus, equipped_by) in (defense_bonuses, equipped).join() {
+        if equipped_by.owner == target {
+            armor_class += defense_bonus.defense;
+        }
+    }
+    armor_class
+}
+
+//natural 1 always misses, natural 20 always crits; returns the result and the modified roll
+pub fn roll_to_hit(
+    rng: &mut RandomNumberGenerator,
+    hit_bonus: i32,
+    armor_class: i32,
+) -> (AttackResult, i32) {
+    let natural_roll = rng.roll_dice(1, 20);
+    let attack_roll = natural_roll + hit_bonus;
+    let result = if natural_roll == 1 {
+        AttackResult::Fumble
+    } else if natural_roll == 20 {
+        AttackResult::Critical
+    } else if attack_roll >= armor_class {
+        AttackResult::Hit
+    } else {
+        AttackResult::Miss
+    };
+    (result, attack_roll)
+}
+
+pub fn roll_damage(
+    rng: &mut RandomNumberGenerator,
+    result: AttackResult,
+    n_dice: i32,
+    die_type: i32,
+    bonus: i32,
+) -> i32 {
+    let mut damage = rng.roll_dice(n_dice, die_type);
+    if result == AttackResult::Critical {
+        damage += rng.roll_dice(n_dice, die_type);
+    }
+    i32::max(1, damage + bonus)
+}
+
+//10 + quickness, less a point for every point of defense from worn armour, shields and helms
+pub fn stealth_dc(
+    sneak: Entity,
+    attributes: &ReadStorage<Attributes>,
+    defense_bonuses: &ReadStorage<DefenseBonus>,
+    equipped: &ReadStorage<Equipped>,
+) -> i32 {
+    let mut dc = 10 + attributes.get(sneak).map_or(0, |a| a.quickness.modifier());
+    for (defense_bonus, equipped_by) in (defense_bonuses, equipped).join() {
+        let noisy = matches!(
+            equipped_by.slot,
+            EquipmentSlot::Armor | EquipmentSlot::Shield | EquipmentSlot::Helm
+        );
+        if equipped_by.owner == sneak && noisy {
+            //cursed gear never helps
+            dc -= i32::max(0, defense_bonus.defense);
+        }
+    }
+    dc
+}
+
+//d20 + intelligence, harder the further away the sneak is and much harder when asleep
+pub fn perception_roll(
+    rng: &mut RandomNumberGenerator,
+    intelligence_bonus: i32,
+    distance: f32,
+    asleep: bool,
+) -> i32 {
+    let mut roll = rng.roll_dice(1, 20) + intelligence_bonus - (distance / 2.0) as i32;
+    if asleep {
+        roll -= 5;
+    }
+    roll
+}
+
+//might does the lifting, fitness lets you keep it up
+pub fn carry_capacity(attrs: &Attributes) -> f32 {
+    (attrs.might.base * 3 + attrs.fitness.base) as f32
+}
+
+//everything in the backpack or worn counts towards the load, stacks by the piece
+pub fn carried_weight<D, S>(
+    owner: Entity,
+    items: &ReadStorage<Item>,
+    backpack: &Storage<InBackpack, D>,
+    equipped: &ReadStorage<Equipped>,
+    stacks: &Storage<Stackable, S>,
+) -> f32
+where
+    D: Deref<Target = MaskedStorage<InBackpack>>,
+    S: Deref<Target = MaskedStorage<Stackable>>,
+{
+    let packed: f32 = (items, backpack, stacks.maybe())
+        .join()
+        .filter(|(_item, pack, _stack)| pack.owner == owner)
+        .map(|(item, _pack, stack)| item.weight * stack.map_or(1, |s| s.quantity) as f32)
+        .sum();
+    let worn: f32 = (items, equipped)
+        .join()
+        .filter(|(_item, equipped_by)| equipped_by.owner == owner)
+        .map(|(item, _equipped_by)| item.weight)
+        .sum();
+    packed + worn
+}
+
+//-1 to hit once over capacity, and another -1 for every further quarter of capacity carried
+pub fn encumbrance_penalty<D, S>(
+    owner: Entity,
+    attributes: &ReadStorage<Attributes>,
+    items: &ReadStorage<Item>,
+    backpack: &Storage<InBackpack, D>,
+    equipped: &ReadStorage<Equipped>,
+    stacks: &Storage<Stackable, S>,
+) -> i32
+where
+    D: Deref<Target = MaskedStorage<InBackpack>>,
+    S: Deref<Target = MaskedStorage<Stackable>>,
+{
+    let capacity = match attributes.get(owner) {
+        None => return 0,
+        Some(attrs) => carry_capacity(attrs),
+    };
+    let carried = carried_weight(owner, items, backpack, equipped, stacks);
+    if carried <= capacity {
+        return 0;
+    }
+    1 + ((carried - capacity) / (capacity / 4.0)) as i32
+}
@@ -1,3 +1,5 @@
+use crate::faction::FactionId;
+use crate::random_table::RandomTable;
 use rltk::RGB;
 use specs::prelude::*;
 use specs_derive::*;
@@ -29,6 +31,93 @@ pub struct Viewshed {
 #[derive(Component)]
 pub struct Monster {}
 
+#[derive(Component, Debug)]
+pub struct Ally {}
+
+#[derive(Component, Debug)]
+pub struct Unaware {}
+
+#[derive(Component, Debug)]
+pub struct Morale {
+    pub value: i32,
+    pub max: i32,
+}
+
+//a monster that has thrown down its arms; nobody fights it until it takes them up again
+#[derive(Component, Debug)]
+pub struct Surrendered {}
+
+#[derive(Component, Debug)]
+pub struct Faction {
+    pub id: FactionId,
+}
+
+#[derive(Clone, Debug)]
+pub struct Spell {
+    pub name: String,
+    pub damage: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Clone, Debug)]
+pub enum AbilityKind {
+    Shoot,
+    Cast(Spell),
+    Summon(String),
+}
+
+#[derive(Clone, Debug)]
+pub struct MonsterAbility {
+    pub kind: AbilityKind,
+    pub range: i32,
+    pub chance: i32,
+    pub cooldown: i32,
+    pub cooldown_remaining: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Abilities {
+    pub abilities: Vec<MonsterAbility>,
+}
+
+#[derive(Component, Debug)]
+pub struct WantsToCastSpell {
+    pub spell: Spell,
+    pub target: rltk::Point,
+}
+
+#[derive(Component, Debug)]
+pub struct WantsToSummon {
+    pub monster: String,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AiState {
+    Idle,
+    Wandering,
+    Chasing,
+    Searching,
+    Fleeing,
+}
+
+#[derive(Component, Debug)]
+pub struct MonsterMemory {
+    pub state: AiState,
+    pub last_seen: Option<rltk::Point>,
+    pub search_turns: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct GroupMember {
+    pub group_id: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct GroupLeader {}
+
+#[derive(Component, Debug)]
+pub struct Unique {}
+
 #[derive(Component)]
 pub struct Named {
     pub name: String,
@@ -41,37 +130,206 @@ pub struct BlocksTile {}
 pub struct CombatStats {
     pub max_hp: i32,
     pub hp: i32,
-    pub defense: i32,
-    pub power: i32,
 }
+
+#[derive(Debug, Clone)]
+pub struct Attribute {
+    pub base: i32,
+}
+
+impl Attribute {
+    pub fn new(base: i32) -> Attribute {
+        Attribute { base }
+    }
+
+    //d20 style modifier: 10-11 is +0, every two points either side moves it by one
+    pub fn modifier(&self) -> i32 {
+        (self.base - 10).div_euclid(2)
+    }
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Attributes {
+    pub might: Attribute,
+    pub fitness: Attribute,
+    pub quickness: Attribute,
+    pub intelligence: Attribute,
+}
+
+impl Attributes {
+    pub fn new(might: i32, fitness: i32, quickness: i32, intelligence: i32) -> Attributes {
+        Attributes {
+            might: Attribute::new(might),
+            fitness: Attribute::new(fitness),
+            quickness: Attribute::new(quickness),
+            intelligence: Attribute::new(intelligence),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NaturalAttack {
+    pub name: String,
+    pub hit_bonus: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct NaturalAttackDefense {
+    pub armor_class: Option<i32>,
+    pub attacks: Vec<NaturalAttack>,
+}
+
+#[derive(Component, Debug)]
+pub struct Experience {
+    pub level: i32,
+    pub xp: i32,
+    pub pending_level_ups: i32,
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LevelUpChoice {
+    Might,
+    Fitness,
+    Quickness,
+    Intelligence,
+    Toughness,
+}
+
+impl LevelUpChoice {
+    pub fn description(&self) -> &'static str {
+        match self {
+            LevelUpChoice::Might => "+1 Might",
+            LevelUpChoice::Fitness => "+1 Fitness",
+            LevelUpChoice::Quickness => "+1 Quickness",
+            LevelUpChoice::Intelligence => "+1 Intelligence",
+            LevelUpChoice::Toughness => "Toughness (+10 max hp)",
+        }
+    }
+}
+
+impl Experience {
+    pub fn xp_to_next_level(&self) -> i32 {
+        self.level * 50
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct WantsToMelee {
     pub target: Entity,
 }
 
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum DamageType {
+    Bludgeoning,
+    Slashing,
+    Piercing,
+    Force,
+    Fire,
+    Cold,
+    Poison,
+    Starvation,
+}
+
+impl DamageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DamageType::Bludgeoning => "bludgeoning",
+            DamageType::Slashing => "slashing",
+            DamageType::Piercing => "piercing",
+            DamageType::Force => "force",
+            DamageType::Fire => "fire",
+            DamageType::Cold => "cold",
+            DamageType::Poison => "poison",
+            DamageType::Starvation => "starvation",
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct Resistances {
+    pub damage_types: Vec<DamageType>,
+}
+
+#[derive(Component, Debug)]
+pub struct Immunities {
+    pub damage_types: Vec<DamageType>,
+}
+
+#[derive(Component, Debug)]
+pub struct Vulnerabilities {
+    pub damage_types: Vec<DamageType>,
+}
+
 #[derive(Component, Debug)]
 pub struct SufferDamage {
-    pub amount: Vec<i32>,
+    pub amount: Vec<(i32, DamageType, Option<Entity>)>,
 }
 
 impl SufferDamage {
-    pub fn new_damage(store: &mut WriteStorage<SufferDamage>, victim: Entity, amount: i32) {
+    pub fn new_damage(
+        store: &mut WriteStorage<SufferDamage>,
+        victim: Entity,
+        amount: i32,
+        damage_type: DamageType,
+        source: Option<Entity>,
+    ) {
         if let Some(suffering) = store.get_mut(victim) {
-            suffering.amount.push(amount);
+            suffering.amount.push((amount, damage_type, source));
         } else {
             let dmg = SufferDamage {
-                amount: vec![amount],
+                amount: vec![(amount, damage_type, source)],
             };
             store.insert(victim, dmg).expect("unable to insert damage");
         }
     }
 }
+//weight is in pounds, value in gold pieces
+#[derive(Component, Debug)]
+pub struct Item {
+    pub weight: f32,
+    pub value: i32,
+}
+
+//identical items carried together share one entity and count
+#[derive(Component, Debug, Clone)]
+pub struct Stackable {
+    pub quantity: i32,
+}
+
 #[derive(Component, Debug)]
-pub struct Item {}
+pub struct ProvidesIdentification {}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum MagicItemClass {
+    Potion,
+    Scroll,
+}
 
+//goes by a made up appearance until the player learns what it is
 #[derive(Component, Debug)]
-pub struct Potion {
-    pub heal_amount: i32,
+pub struct MagicItem {
+    pub class: MagicItemClass,
+}
+
+//a cursed item can't be taken off once worn; revealed once the player knows about it
+#[derive(Component, Debug, Clone)]
+pub struct Cursed {
+    pub revealed: bool,
+}
+
+#[derive(Component, Debug)]
+pub struct RemovesCurse {}
+
+#[derive(Component, Debug)]
+pub struct RevealsCurses {}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToIdentify {
+    pub item: Entity,
 }
 
 #[derive(Component, Debug, Clone)]
@@ -95,6 +353,11 @@ pub struct WantsToDropItem {
     pub item: Entity,
 }
 
+#[derive(Component, Debug, Clone)]
+pub struct WantsToRemoveItem {
+    pub item: Entity,
+}
+
 #[derive(Component, Debug)]
 pub struct Consumable {}
 
@@ -108,7 +371,198 @@ pub struct Ranged {
     pub range: i32,
 }
 
+#[derive(Component, Debug)]
+pub struct AreaOfEffect {
+    pub radius: i32,
+}
+
 #[derive(Component, Debug)]
 pub struct InflictsDamage {
     pub damage: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug)]
+pub struct LootTable {
+    pub drop_chance: i32,
+    pub table: RandomTable,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum EquipmentSlot {
+    Melee,
+    Ranged,
+    Shield,
+    Armor,
+    Helm,
+    Ring,
+    Amulet,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Equippable {
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Equipped {
+    pub owner: Entity,
+    pub slot: EquipmentSlot,
+}
+
+#[derive(Component, Debug)]
+pub struct MeleeWeapon {
+    pub hit_bonus: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AmmoType {
+    Arrow,
+    SlingStone,
+}
+
+impl AmmoType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AmmoType::Arrow => "arrows",
+            AmmoType::SlingStone => "sling stones",
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct RangedWeapon {
+    pub range: i32,
+    pub ammo: AmmoType,
+    pub hit_bonus: i32,
+    pub damage_n_dice: i32,
+    pub damage_die_type: i32,
+    pub damage_bonus: i32,
+    pub damage_type: DamageType,
+}
+
+#[derive(Component, Debug)]
+pub struct Ammunition {
+    pub ammo_type: AmmoType,
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct WantsToShoot {
+    pub target: rltk::Point,
+}
+
+#[derive(Component, Debug)]
+pub struct MeleePowerBonus {
+    pub power: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct DefenseBonus {
+    pub defense: i32,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum StatusEffect {
+    Confusion,
+    Poison { damage: i32 },
+    Sleep,
+    Paralysis,
+    Haste,
+}
+
+impl StatusEffect {
+    pub fn adjective(&self) -> &'static str {
+        match self {
+            StatusEffect::Confusion => "confused",
+            StatusEffect::Poison { .. } => "poisoned",
+            StatusEffect::Sleep => "asleep",
+            StatusEffect::Paralysis => "paralyzed",
+            StatusEffect::Haste => "hasted",
+        }
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct AppliesStatus {
+    pub effect: StatusEffect,
+    pub turns: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Confused {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Poisoned {
+    pub turns: i32,
+    pub damage: i32,
+    pub source: Option<Entity>,
+}
+
+#[derive(Component, Debug)]
+pub struct Asleep {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Paralyzed {
+    pub turns: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Hasted {
+    pub turns: i32,
+    pub free_action: bool,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum HungerState {
+    WellFed,
+    Normal,
+    Hungry,
+    Starving,
+}
+
+impl HungerState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HungerState::WellFed => "Well Fed",
+            HungerState::Normal => "Normal",
+            HungerState::Hungry => "Hungry",
+            HungerState::Starving => "Starving",
+        }
+    }
+
+    //natural healing needs something in the stomach
+    pub fn stops_healing(&self) -> bool {
+        matches!(self, HungerState::Hungry | HungerState::Starving)
+    }
+}
+
+#[derive(Component, Debug)]
+pub struct HungerClock {
+    pub state: HungerState,
+    pub duration: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct ProvidesFood {}
+
+#[derive(Component, Debug)]
+pub struct Regenerates {
+    pub interval: i32,
+    pub counter: i32,
+}
+
+#[derive(Component, Debug)]
+pub struct Resting {}
+
+#[derive(Component, Debug)]
+pub struct ParticleLifetime {
+    pub lifetime_ms: f32,
 }
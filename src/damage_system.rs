@@ -1,23 +1,140 @@
-use crate::{CombatStats, GameLog, Named, Player, SufferDamage};
-use rltk::console;
+use crate::particle_system::ParticleBuilder;
+use crate::spawner::{self, UniqueRegistry};
+use crate::{
+    Asleep, Attributes, CombatStats, Equipped, Experience, Faction, GameLog, GroupLeader,
+    GroupMember, Immunities, InBackpack, LootTable, Map, Morale, Named, Player, Position,
+    Resistances, SufferDamage, Surrendered, Unaware, Unique, Viewshed, Vulnerabilities,
+};
+use rltk::{console, Point, RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 pub struct DamageSystem {}
 
 impl<'a> System<'a> for DamageSystem {
     type SystemData = (
+        Entities<'a>,
         WriteStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Resistances>,
+        ReadStorage<'a, Immunities>,
+        ReadStorage<'a, Vulnerabilities>,
+        ReadStorage<'a, Named>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, Asleep>,
+        WriteStorage<'a, Experience>,
+        ReadStorage<'a, Attributes>,
+        ReadExpect<'a, Entity>,
+        WriteStorage<'a, Unaware>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+        WriteStorage<'a, Surrendered>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (mut stats, mut damage) = data;
-        for (stats, damage) in (&mut stats, &damage).join() {
-            stats.hp -= damage.amount.iter().sum::<i32>();
+        let (
+            entities,
+            mut stats,
+            mut damage,
+            resistances,
+            immunities,
+            vulnerabilities,
+            names,
+            mut log,
+            mut asleep,
+            mut experience,
+            attributes,
+            player_entity,
+            mut unaware,
+            map,
+            positions,
+            mut surrendered,
+        ) = data;
+        let mut xp_awards: Vec<(Entity, i32)> = Vec::new();
+        for (entity, stats, damage) in (&entities, &mut stats, &damage).join() {
+            let seen = map.player_sees(&positions, entity);
+            for (amount, damage_type, source) in damage.amount.iter() {
+                let mut amount = *amount;
+                let mut note: Option<&str> = None;
+                if immunities
+                    .get(entity)
+                    .is_some_and(|i| i.damage_types.contains(damage_type))
+                {
+                    amount = 0;
+                    note = Some("is immune to");
+                } else if resistances
+                    .get(entity)
+                    .is_some_and(|r| r.damage_types.contains(damage_type))
+                {
+                    amount /= 2;
+                    note = Some("resists");
+                } else if vulnerabilities
+                    .get(entity)
+                    .is_some_and(|v| v.damage_types.contains(damage_type))
+                {
+                    amount *= 2;
+                    note = Some("is vulnerable to");
+                }
+                if let (Some(note), Some(name), true) = (note, names.get(entity), seen) {
+                    log.entries.push(format!(
+                        "{} {} {} damage.",
+                        &name.name,
+                        note,
+                        damage_type.name()
+                    ));
+                }
+                let was_alive = stats.hp > 0;
+                stats.hp -= amount;
+                //whoever lands the killing blow gets the credit
+                if was_alive && stats.hp < 1 {
+                    if let Some(killer) = source {
+                        xp_awards.push((*killer, stats.max_hp));
+                    }
+                }
+                if amount > 0 {
+                    unaware.remove(entity);
+                }
+                //hurting one that gave up makes it a foe again, free to run or fight
+                if amount > 0 && surrendered.remove(entity).is_some() && seen {
+                    if let Some(name) = names.get(entity) {
+                        log.entries
+                            .push(format!("{} takes up its arms again!", &name.name));
+                    }
+                }
+                if amount > 0 && asleep.remove(entity).is_some() && seen {
+                    if let Some(name) = names.get(entity) {
+                        log.entries.push(format!("{} wakes up!", &name.name));
+                    }
+                }
+            }
         }
         damage.clear();
+
+        for (killer, xp) in xp_awards {
+            if let Some(exp) = experience.get_mut(killer) {
+                exp.xp += xp;
+                if killer == *player_entity {
+                    log.entries.push(format!("You gain {} xp.", xp));
+                }
+                while exp.xp >= exp.xp_to_next_level() {
+                    exp.xp -= exp.xp_to_next_level();
+                    exp.level += 1;
+                    exp.pending_level_ups += 1;
+                    //hit dice of 5, plus fitness, but always at least one hp
+                    let fitness = attributes.get(killer).map_or(0, |a| a.fitness.modifier());
+                    let hp_gain = i32::max(1, 5 + fitness);
+                    if let Some(killer_stats) = stats.get_mut(killer) {
+                        killer_stats.max_hp += hp_gain;
+                        killer_stats.hp += hp_gain;
+                    }
+                    if killer == *player_entity {
+                        log.entries.push(format!("Welcome to level {}!", exp.level));
+                    }
+                }
+            }
+        }
     }
 }
+
 pub fn delete_the_dead(ecs: &mut World) {
     let mut dead: Vec<Entity> = Vec::new();
 
@@ -26,16 +143,30 @@ pub fn delete_the_dead(ecs: &mut World) {
         let players = ecs.read_storage::<Player>();
         let entities = ecs.entities();
         let names = ecs.read_storage::<Named>();
+        let uniques = ecs.read_storage::<Unique>();
         let mut log = ecs.write_resource::<GameLog>();
+        let mut registry = ecs.write_resource::<UniqueRegistry>();
+        let positions = ecs.read_storage::<Position>();
+        let map = ecs.fetch::<Map>();
         for (entity, stats) in (&entities, &combat_stats).join() {
             if stats.hp < 1 {
                 let player = players.get(entity);
                 match player {
                     None => {
                         let victim_name = names.get(entity);
+                        let seen = map.player_sees(&positions, entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries
-                                .push(format!("{} fucking DIED", &victim_name.name));
+                            if seen {
+                                log.entries
+                                    .push(format!("{} fucking DIED", &victim_name.name));
+                            }
+                            if uniques.get(entity).is_some() {
+                                if seen {
+                                    log.entries
+                                        .push(format!("{} has been slain!", &victim_name.name));
+                                }
+                                registry.slain.push(victim_name.name.clone());
+                            }
                         }
                         dead.push(entity)
                     }
@@ -44,7 +175,96 @@ pub fn delete_the_dead(ecs: &mut World) {
             }
         }
     }
+
+    //kin who see one of their own fall lose heart, and losing a leader hits the whole group
+    {
+        let positions = ecs.read_storage::<Position>();
+        let factions = ecs.read_storage::<Faction>();
+        let group_members = ecs.read_storage::<GroupMember>();
+        let group_leaders = ecs.read_storage::<GroupLeader>();
+        let viewsheds = ecs.read_storage::<Viewshed>();
+        let mut morales = ecs.write_storage::<Morale>();
+        for victim in dead.iter() {
+            let (victim_pos, victim_faction) = match (positions.get(*victim), factions.get(*victim))
+            {
+                (Some(pos), Some(faction)) => (Point::new(pos.x, pos.y), faction),
+                _ => continue,
+            };
+            let fallen_leader = group_leaders
+                .get(*victim)
+                .and_then(|_| group_members.get(*victim))
+                .map(|member| member.group_id);
+            for (morale, viewshed, faction, member) in
+                (&mut morales, &viewsheds, &factions, group_members.maybe()).join()
+            {
+                if faction.id != victim_faction.id {
+                    continue;
+                }
+                if viewshed.visible_tiles.contains(&victim_pos) {
+                    morale.value -= 3;
+                }
+                if fallen_leader.is_some() && member.map(|m| m.group_id) == fallen_leader {
+                    morale.value -= 6;
+                }
+            }
+        }
+    }
+
+    //carried items and a roll on the loot table land where the victim fell
+    let mut to_spawn: Vec<(String, i32, i32)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let loot_tables = ecs.read_storage::<LootTable>();
+        let mut positions = ecs.write_storage::<Position>();
+        let mut backpack = ecs.write_storage::<InBackpack>();
+        let mut equipped = ecs.write_storage::<Equipped>();
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        let mut particle_builder = ecs.write_resource::<ParticleBuilder>();
+        for victim in dead.iter() {
+            let (x, y) = match positions.get(*victim) {
+                Some(pos) => (pos.x, pos.y),
+                None => continue,
+            };
+            particle_builder.request(
+                x,
+                y,
+                RGB::named(rltk::RED),
+                RGB::named(rltk::BLACK),
+                rltk::to_cp437('%'),
+                400.0,
+            );
+            let mut carried: Vec<Entity> = (&entities, &backpack)
+                .join()
+                .filter(|(_item, pack)| pack.owner == *victim)
+                .map(|(item, _pack)| item)
+                .collect();
+            carried.extend(
+                (&entities, &equipped)
+                    .join()
+                    .filter(|(_item, worn)| worn.owner == *victim)
+                    .map(|(item, _worn)| item),
+            );
+            for item in carried {
+                backpack.remove(item);
+                equipped.remove(item);
+                positions
+                    .insert(item, Position { x, y })
+                    .expect("unable to insert position, dropped loot");
+            }
+            if let Some(loot) = loot_tables.get(*victim) {
+                if rng.roll_dice(1, 100) <= loot.drop_chance {
+                    if let Some(name) = loot.table.roll(&mut rng) {
+                        to_spawn.push((name, x, y));
+                    }
+                }
+            }
+        }
+    }
+
     for victim in dead {
         ecs.delete_entity(victim).expect("unable to delete");
     }
+    for (name, x, y) in to_spawn {
+        spawner::spawn_named_item(ecs, &name, x, y);
+    }
 }
@@ -0,0 +1,41 @@
+#[derive(PartialEq, Eq, Copy, Clone, Debug)]
+pub enum FactionId {
+    Player,
+    Orcs,
+    Goblins,
+    Elementals,
+    Townsfolk,
+}
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum Reaction {
+    Attack,
+    Ignore,
+    Flee,
+}
+
+//how each faction feels about the others, spelled out so a new faction won't build until
+//every pairing has been decided
+pub fn reaction(mine: FactionId, theirs: FactionId) -> Reaction {
+    use FactionId::*;
+    match (mine, theirs) {
+        //kin never fight
+        (Player, Player)
+        | (Orcs, Orcs)
+        | (Goblins, Goblins)
+        | (Elementals, Elementals)
+        | (Townsfolk, Townsfolk) => Reaction::Ignore,
+        (Player, Orcs) | (Player, Goblins) | (Player, Elementals) => Reaction::Attack,
+        (Player, Townsfolk) => Reaction::Ignore,
+        (Orcs, Player) | (Orcs, Goblins) | (Orcs, Townsfolk) => Reaction::Attack,
+        (Orcs, Elementals) => Reaction::Flee,
+        (Goblins, Player) | (Goblins, Orcs) | (Goblins, Townsfolk) => Reaction::Attack,
+        (Goblins, Elementals) => Reaction::Flee,
+        (Elementals, Player)
+        | (Elementals, Orcs)
+        | (Elementals, Goblins)
+        | (Elementals, Townsfolk) => Reaction::Attack,
+        (Townsfolk, Player) => Reaction::Ignore,
+        (Townsfolk, Orcs) | (Townsfolk, Goblins) | (Townsfolk, Elementals) => Reaction::Flee,
+    }
+}
@@ -1,3 +1,11 @@
 pub struct GameLog {
     pub entries: Vec<String>,
 }
+
+//"a Goblin" or "an Orc", going by the first letter
+pub fn with_article(name: &str) -> String {
+    match name.chars().next().map(|c| c.to_ascii_lowercase()) {
+        Some('a' | 'e' | 'i' | 'o' | 'u') => format!("an {}", name),
+        _ => format!("a {}", name),
+    }
+}
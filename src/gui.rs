@@ -1,5 +1,12 @@
-use crate::components::InBackpack;
-use crate::{CombatStats, GameLog, Map, Named, Player, Position, State, Viewshed};
+use crate::combat::{carried_weight, carry_capacity};
+use crate::components::{Cursed, Equipped, InBackpack, Item, Stackable};
+use crate::identification::Identification;
+use crate::inventory_system::stack_name;
+use crate::{
+    AreaOfEffect, Asleep, Attributes, CombatStats, Confused, Experience, GameLog, Hasted,
+    HungerClock, HungerState, LevelUpChoice, Map, Named, Paralyzed, Player, Poisoned, Position,
+    State, Viewshed,
+};
 use rltk::{Point, Rltk, VirtualKeyCode, RGB};
 use specs::prelude::*;
 
@@ -34,21 +41,94 @@ pub fn draw_ui(ecs: &World, ctx: &mut Rltk) {
         ctx.draw_bar_horizontal(
             28,
             43,
-            51,
+            26,
             stat.hp,
             stat.max_hp,
             RGB::named(rltk::RED),
             RGB::named(rltk::BLACK),
         );
     }
-    let log = ecs.fetch::<GameLog>();
+    let attributes = ecs.read_storage::<Attributes>();
+    for (_player, attr) in (&players, &attributes).join() {
+        let attribute_line = format!(
+            "M:{} F:{} Q:{} I:{}",
+            attr.might.base, attr.fitness.base, attr.quickness.base, attr.intelligence.base
+        );
+        ctx.print_color(
+            57,
+            43,
+            RGB::named(rltk::CYAN),
+            RGB::named(rltk::BLACK),
+            &attribute_line,
+        );
+    }
 
-    let mut y = 44;
-    for s in log.entries.iter().rev() {
-        if y < 49 {
-            ctx.print(2, y, s);
+    let experience = ecs.read_storage::<Experience>();
+    for (_player, exp) in (&players, &experience).join() {
+        let level = format!("Level {}", exp.level);
+        ctx.print_color(
+            50,
+            48,
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+            &level,
+        );
+        ctx.draw_bar_horizontal(
+            59,
+            48,
+            19,
+            exp.xp,
+            exp.xp_to_next_level(),
+            RGB::named(rltk::GOLD),
+            RGB::named(rltk::BLACK),
+        );
+    }
+
+    let hunger = ecs.read_storage::<HungerClock>();
+    for (_player, clock) in (&players, &hunger).join() {
+        let color = match clock.state {
+            HungerState::WellFed => RGB::named(rltk::GREEN),
+            HungerState::Normal => RGB::named(rltk::WHITE),
+            HungerState::Hungry => RGB::named(rltk::ORANGE),
+            HungerState::Starving => RGB::named(rltk::RED),
+        };
+        if clock.state != HungerState::Normal {
+            ctx.print_color(2, 43, color, RGB::named(rltk::BLACK), clock.state.name());
         }
-        y += 1;
+    }
+
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut effects: Vec<String> = Vec::new();
+    if let Some(effect) = ecs.read_storage::<Confused>().get(player_entity) {
+        effects.push(format!("Confused({})", effect.turns));
+    }
+    if let Some(effect) = ecs.read_storage::<Poisoned>().get(player_entity) {
+        effects.push(format!("Poisoned({})", effect.turns));
+    }
+    if let Some(effect) = ecs.read_storage::<Asleep>().get(player_entity) {
+        effects.push(format!("Asleep({})", effect.turns));
+    }
+    if let Some(effect) = ecs.read_storage::<Paralyzed>().get(player_entity) {
+        effects.push(format!("Paralyzed({})", effect.turns));
+    }
+    if let Some(effect) = ecs.read_storage::<Hasted>().get(player_entity) {
+        effects.push(format!("Hasted({})", effect.turns));
+    }
+    if !effects.is_empty() {
+        ctx.print_color(
+            2,
+            48,
+            RGB::named(rltk::MAGENTA),
+            RGB::named(rltk::BLACK),
+            effects.join(" "),
+        );
+    }
+
+    //the log gets the rows above the status line
+    let log = ecs.fetch::<GameLog>();
+
+    for (y, s) in (44..48).zip(log.entries.iter().rev()) {
+        ctx.print(2, y, s);
     }
 
     draw_tooltips(ecs, ctx);
@@ -58,6 +138,7 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     let map = ecs.fetch::<Map>();
     let names = ecs.read_storage::<Named>();
     let positions = ecs.read_storage::<Position>();
+    let identification = ecs.fetch::<Identification>();
 
     let mouse_pos = ctx.mouse_pos();
     if mouse_pos.0 >= map.width || mouse_pos.1 >= map.height {
@@ -70,7 +151,7 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
     for (name, pos) in (&names, &positions).join() {
         let idx = map.xy_idx(pos.x, pos.y);
         if pos.x == mouse_pos.0 && pos.y == mouse_pos.1 && map.visible_tiles[idx] {
-            tooltip.push(name.name.to_string());
+            tooltip.push(identification.display_name(&name.name));
         }
     }
 
@@ -86,8 +167,7 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
         if mouse_pos.0 > 40 {
             let arrow_pos = Point::new(mouse_pos.0 - 2, mouse_pos.1);
             let left_x = mouse_pos.0 - width;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x,
                     y,
@@ -102,23 +182,21 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::BLACK),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::BLACK),
-                &"->".to_string(),
+                "->",
             );
         } else {
             let arrow_pos = Point::new(mouse_pos.0 + 1, mouse_pos.1);
             let left_x = mouse_pos.0 + 3;
-            let mut y = mouse_pos.1;
-            for s in tooltip.iter() {
+            for (y, s) in (mouse_pos.1..).zip(tooltip.iter()) {
                 ctx.print_color(
                     left_x + 1,
                     y,
@@ -133,34 +211,60 @@ pub fn draw_tooltips(ecs: &World, ctx: &mut Rltk) {
                         y,
                         RGB::named(rltk::WHITE),
                         RGB::named(rltk::GREY),
-                        &" ".to_string(),
+                        " ",
                     );
                 }
-                y += 1;
             }
             ctx.print_color(
                 arrow_pos.x,
                 arrow_pos.y,
                 RGB::named(rltk::WHITE),
                 RGB::named(rltk::BLACK),
-                &"<-".to_string(),
+                "<-",
             );
         }
     }
 }
 
+fn cursed_label(label: String, curse: Option<&Cursed>) -> String {
+    match curse {
+        Some(curse) if curse.revealed => format!("{} (cursed)", label),
+        _ => label,
+    }
+}
+
 pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Named>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
+    let items = gs.ecs.read_storage::<Item>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
 
     let inventory = (&backpack, &names)
         .join()
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
+    let stacks = gs.ecs.read_storage::<Stackable>();
+    let identification = gs.ecs.fetch::<Identification>();
+    let cursed = gs.ecs.read_storage::<Cursed>();
+    let carried = carried_weight(*player_entity, &items, &backpack, &equipped, &stacks);
+    let capacity = attributes.get(*player_entity).map_or(0.0, carry_capacity);
+    //worth of everything carried or worn
+    let packed_value: i32 = (&items, &backpack, stacks.maybe())
+        .join()
+        .filter(|(_item, pack, _stack)| pack.owner == *player_entity)
+        .map(|(item, _pack, stack)| item.value * stack.map_or(1, |s| s.quantity))
+        .sum();
+    let worn_value: i32 = (&items, &equipped)
+        .join()
+        .filter(|(_item, equipped_by)| equipped_by.owner == *player_entity)
+        .map(|(item, _equipped_by)| item.value)
+        .sum();
+    let value = packed_value + worn_value;
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -183,13 +287,33 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
         RGB::named(rltk::BLACK),
         "TAB to cancel",
     );
+    let weight_color = if carried > capacity {
+        RGB::named(rltk::RED)
+    } else {
+        RGB::named(rltk::WHITE)
+    };
+    ctx.print_color(
+        30,
+        y - 2,
+        weight_color,
+        RGB::named(rltk::BLACK),
+        format!("{:.1}/{:.0} lbs", carried, capacity),
+    );
+    ctx.print_color(
+        36,
+        y + count as i32 + 1,
+        RGB::named(rltk::GOLD),
+        RGB::named(rltk::BLACK),
+        format!("{} gp", value),
+    );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -212,10 +336,15 @@ pub fn show_inventory(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(
+            21,
+            y,
+            cursed_label(
+                stack_name(&identification.display_name(&name.name), stacks.get(entity)),
+                cursed.get(entity),
+            ),
+        );
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
     match ctx.key {
         None => (ItemMenuResult::NoResponse, None),
@@ -240,13 +369,16 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     let names = gs.ecs.read_storage::<Named>();
     let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
+    let stacks = gs.ecs.read_storage::<Stackable>();
+    let identification = gs.ecs.fetch::<Identification>();
+    let cursed = gs.ecs.read_storage::<Cursed>();
 
     let inventory = (&backpack, &names)
         .join()
         .filter(|item| item.0.owner == *player_entity);
     let count = inventory.count();
 
-    let mut y = (25 - (count / 2)) as i32;
+    let y = (25 - (count / 2)) as i32;
     ctx.draw_box(
         15,
         y - 2,
@@ -271,11 +403,12 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     );
 
     let mut equippable: Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names)
+    for (j, (entity, _pack, name)) in (&entities, &backpack, &names)
         .join()
         .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
     {
+        let y = y + j as i32;
         ctx.set(
             17,
             y,
@@ -298,10 +431,15 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
             rltk::to_cp437(')'),
         );
 
-        ctx.print(21, y, &name.name.to_string());
+        ctx.print(
+            21,
+            y,
+            cursed_label(
+                stack_name(&identification.display_name(&name.name), stacks.get(entity)),
+                cursed.get(entity),
+            ),
+        );
         equippable.push(entity);
-        y += 1;
-        j += 1;
     }
 
     match ctx.key {
@@ -322,14 +460,354 @@ pub fn drop_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option
     }
 }
 
+//what the player carries that is still unknown, other than the identify scroll's own kind
+pub fn unidentified_items(ecs: &World, scroll: Entity) -> Vec<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Named>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+    let identification = ecs.fetch::<Identification>();
+    let scroll_name = names.get(scroll).map_or("", |n| n.name.as_str());
+
+    (&entities, &backpack, &names)
+        .join()
+        .filter(|(_item, pack, name)| {
+            pack.owner == *player_entity
+                && name.name != scroll_name
+                && !identification.is_identified(&name.name)
+        })
+        .map(|(item, _pack, _name)| item)
+        .collect()
+}
+
+pub fn identify_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    scroll: Entity,
+) -> (ItemMenuResult, Option<Entity>) {
+    let names = gs.ecs.read_storage::<Named>();
+    let stacks = gs.ecs.read_storage::<Stackable>();
+    let identification = gs.ecs.fetch::<Identification>();
+
+    let unknown = unidentified_items(&gs.ecs, scroll);
+    let count = unknown.len();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Identify Which Item?",
+    );
+    ctx.print_color(
+        18,
+        y + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "TAB to cancel",
+    );
+
+    for (j, entity) in unknown.iter().enumerate() {
+        let y = y + j as i32;
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+
+        ctx.print(
+            21,
+            y,
+            stack_name(
+                &identification.display_name(&names.get(*entity).unwrap().name),
+                stacks.get(*entity),
+            ),
+        );
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Tab => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (ItemMenuResult::Selected, Some(unknown[selection as usize]));
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+//left and right pick how many to drop, enter confirms
+pub fn drop_quantity_menu(
+    gs: &mut State,
+    ctx: &mut Rltk,
+    item: Entity,
+    quantity: i32,
+) -> (ItemMenuResult, i32) {
+    let names = gs.ecs.read_storage::<Named>();
+    let stacks = gs.ecs.read_storage::<Stackable>();
+    let identification = gs.ecs.fetch::<Identification>();
+    let available = stacks.get(item).map_or(1, |stack| stack.quantity);
+    let name = names.get(item).map_or("something".to_string(), |n| {
+        identification.display_name(&n.name)
+    });
+
+    let y = 24;
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        4,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Drop How Many?",
+    );
+    ctx.print(18, y, &name);
+    ctx.print_color(
+        18,
+        y + 1,
+        RGB::named(rltk::CYAN),
+        RGB::named(rltk::BLACK),
+        format!("< {} of {} >", quantity, available),
+    );
+    ctx.print_color(
+        18,
+        y + 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "ENTER to drop, TAB to cancel",
+    );
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, quantity),
+        Some(key) => match key {
+            VirtualKeyCode::Tab => (ItemMenuResult::Cancel, quantity),
+            VirtualKeyCode::Return => (ItemMenuResult::Selected, quantity),
+            VirtualKeyCode::Left | VirtualKeyCode::Numpad4 | VirtualKeyCode::H => {
+                (ItemMenuResult::NoResponse, i32::max(1, quantity - 1))
+            }
+            VirtualKeyCode::Right | VirtualKeyCode::Numpad6 | VirtualKeyCode::L => (
+                ItemMenuResult::NoResponse,
+                i32::min(available, quantity + 1),
+            ),
+            _ => (ItemMenuResult::NoResponse, quantity),
+        },
+    }
+}
+
+pub fn remove_item_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let names = gs.ecs.read_storage::<Named>();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let entities = gs.ecs.entities();
+    let cursed = gs.ecs.read_storage::<Cursed>();
+
+    let inventory = (&equipped, &names)
+        .join()
+        .filter(|item| item.0.owner == *player_entity);
+    let count = inventory.count();
+
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Remove Which Item?",
+    );
+    ctx.print_color(
+        18,
+        y + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "TAB to cancel",
+    );
+
+    let mut equippable: Vec<Entity> = Vec::new();
+    for (j, (entity, _pack, name)) in (&entities, &equipped, &names)
+        .join()
+        .filter(|item| item.1.owner == *player_entity)
+        .enumerate()
+    {
+        let y = y + j as i32;
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+
+        ctx.print(21, y, cursed_label(name.name.clone(), cursed.get(entity)));
+        equippable.push(entity);
+    }
+
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => match key {
+            VirtualKeyCode::Tab => (ItemMenuResult::Cancel, None),
+            _ => {
+                let selection = rltk::letter_to_option(key);
+                if selection > -1 && selection < count as i32 {
+                    return (
+                        ItemMenuResult::Selected,
+                        Some(equippable[selection as usize]),
+                    );
+                }
+                (ItemMenuResult::NoResponse, None)
+            }
+        },
+    }
+}
+
+pub fn level_up_menu(gs: &mut State, ctx: &mut Rltk) -> (ItemMenuResult, Option<LevelUpChoice>) {
+    let choices = [
+        LevelUpChoice::Might,
+        LevelUpChoice::Fitness,
+        LevelUpChoice::Quickness,
+        LevelUpChoice::Intelligence,
+        LevelUpChoice::Toughness,
+    ];
+    let player_entity = gs.ecs.fetch::<Entity>();
+    let level = gs
+        .ecs
+        .read_storage::<Experience>()
+        .get(*player_entity)
+        .map_or(1, |exp| exp.level);
+
+    let count = choices.len();
+    let y = (25 - (count / 2)) as i32;
+    ctx.draw_box(
+        15,
+        y - 2,
+        31,
+        (count + 3) as i32,
+        RGB::named(rltk::WHITE),
+        RGB::named(rltk::BLACK),
+    );
+    ctx.print_color(
+        18,
+        y - 2,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        format!("Level {}!", level),
+    );
+    ctx.print_color(
+        18,
+        y + count as i32 + 1,
+        RGB::named(rltk::YELLOW),
+        RGB::named(rltk::BLACK),
+        "Choose an improvement",
+    );
+
+    for (j, choice) in choices.iter().enumerate() {
+        let y = y + j as i32;
+        ctx.set(
+            17,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437('('),
+        );
+        ctx.set(
+            18,
+            y,
+            RGB::named(rltk::YELLOW),
+            RGB::named(rltk::BLACK),
+            97 + j as rltk::FontCharType,
+        );
+        ctx.set(
+            19,
+            y,
+            RGB::named(rltk::WHITE),
+            RGB::named(rltk::BLACK),
+            rltk::to_cp437(')'),
+        );
+
+        ctx.print(21, y, choice.description());
+    }
+    //no cancelling out of a level up
+    match ctx.key {
+        None => (ItemMenuResult::NoResponse, None),
+        Some(key) => {
+            let selection = rltk::letter_to_option(key);
+            if selection > -1 && selection < count as i32 {
+                return (ItemMenuResult::Selected, Some(choices[selection as usize]));
+            }
+            (ItemMenuResult::NoResponse, None)
+        }
+    }
+}
+
 pub fn ranged_target(
     gs: &mut State,
     ctx: &mut Rltk,
     range: i32,
+    item: Entity,
 ) -> (ItemMenuResult, Option<Point>) {
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
     let viewsheds = gs.ecs.read_storage::<Viewshed>();
+    let aoe = gs.ecs.read_storage::<AreaOfEffect>();
+    let map = gs.ecs.fetch::<Map>();
+    let names = gs.ecs.read_storage::<Named>();
+    let identification = gs.ecs.fetch::<Identification>();
 
     ctx.print_color(
         5,
@@ -362,8 +840,24 @@ pub fn ranged_target(
             valid_target = true;
         }
     }
-    if valid_target {
+    //show where the projectile will actually land; nothing gets past a wall right beside you
+    let path = map.line_of_fire(*player_pos, Point::new(mouse_pos.0, mouse_pos.1));
+    let impact = path.last().copied();
+    if let (true, Some(impact)) = (valid_target, impact) {
+        for tile in path.iter() {
+            ctx.set_bg(tile.x, tile.y, RGB::named(rltk::YELLOW));
+        }
+        //an unknown scroll doesn't let on that it will go off with a bang
+        let known = names
+            .get(item)
+            .is_some_and(|name| identification.is_identified(&name.name));
+        if let (Some(area), true) = (aoe.get(item), known) {
+            for tile in map.area_of_effect(impact, area.radius).iter() {
+                ctx.set_bg(tile.x, tile.y, RGB::named(rltk::ORANGE));
+            }
+        }
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
+        ctx.set_bg(impact.x, impact.y, RGB::named(rltk::RED));
         if ctx.left_click {
             return (
                 ItemMenuResult::Selected,
@@ -372,7 +866,13 @@ pub fn ranged_target(
         }
     } else {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::RED));
-        if ctx.left_click {
+        //a wall in the way still lets you try, and the shot goes nowhere
+        if ctx.left_click && valid_target {
+            return (
+                ItemMenuResult::Selected,
+                Some(Point::new(mouse_pos.0, mouse_pos.1)),
+            );
+        } else if ctx.left_click {
             return (ItemMenuResult::Cancel, None);
         }
     }
@@ -0,0 +1,70 @@
+use crate::{DamageType, GameLog, HungerClock, HungerState, RunState, SufferDamage};
+use specs::prelude::*;
+
+pub struct HungerSystem {}
+
+impl<'a> System<'a> for HungerSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        WriteStorage<'a, HungerClock>,
+        WriteStorage<'a, SufferDamage>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (entities, player_entity, runstate, mut log, mut hunger_clocks, mut suffer_damage) =
+            data;
+
+        //the clock ticks once per round, alongside status effects
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, clock) in (&entities, &mut hunger_clocks).join() {
+            clock.duration -= 1;
+            if clock.duration > 0 {
+                continue;
+            }
+            let is_player = entity == *player_entity;
+            match clock.state {
+                HungerState::WellFed => {
+                    clock.state = HungerState::Normal;
+                    clock.duration = 200;
+                    if is_player {
+                        log.entries.push("You are no longer well fed.".to_string());
+                    }
+                }
+                HungerState::Normal => {
+                    clock.state = HungerState::Hungry;
+                    clock.duration = 200;
+                    if is_player {
+                        log.entries.push("You are hungry.".to_string());
+                    }
+                }
+                HungerState::Hungry => {
+                    clock.state = HungerState::Starving;
+                    clock.duration = 0;
+                    if is_player {
+                        log.entries.push("You are starving!".to_string());
+                    }
+                }
+                HungerState::Starving => {
+                    //stays starving until fed, losing a hit point each round
+                    if is_player {
+                        log.entries
+                            .push("Your hunger pangs are getting painful!".to_string());
+                    }
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        entity,
+                        1,
+                        DamageType::Starvation,
+                        None,
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::MagicItemClass;
+use rltk::{RandomNumberGenerator, RGB};
+use std::collections::{HashMap, HashSet};
+
+const POTION_ADJECTIVES: [&str; 8] = [
+    "bubbling", "murky", "fizzing", "cloudy", "smoking", "glowing", "swirling", "thick",
+];
+const POTION_COLOURS: [(&str, (u8, u8, u8)); 8] = [
+    ("red", rltk::RED),
+    ("green", rltk::GREEN),
+    ("blue", rltk::BLUE),
+    ("yellow", rltk::YELLOW),
+    ("violet", rltk::VIOLET),
+    ("black", rltk::SLATE_GRAY),
+    ("orange", rltk::ORANGE),
+    ("silver", rltk::SILVER),
+];
+//every unknown scroll looks like plain parchment
+const SCROLL_COLOUR: (u8, u8, u8) = rltk::WHEAT;
+const SCROLL_SYLLABLES: [&str; 16] = [
+    "ZEL", "GO", "MER", "FOO", "BAR", "VE", "FORB", "XIX", "ELAM", "KER", "NOD", "THARR", "YUM",
+    "VEN", "ZAR", "JU",
+];
+
+//which potions and scrolls the player has learned, and what the rest look like until then
+#[derive(Default)]
+pub struct Identification {
+    appearances: HashMap<String, (String, RGB)>,
+    identified: HashSet<String>,
+    potion_looks: Vec<(String, RGB)>,
+    potions_seen: usize,
+}
+
+impl Identification {
+    //potion adjectives and colours are shuffled once per game
+    pub fn new(rng: &mut RandomNumberGenerator) -> Identification {
+        let mut adjectives: Vec<&str> = POTION_ADJECTIVES.to_vec();
+        let mut colours: Vec<(&str, (u8, u8, u8))> = POTION_COLOURS.to_vec();
+        shuffle(&mut adjectives, rng);
+        shuffle(&mut colours, rng);
+        let potion_looks = adjectives
+            .iter()
+            .zip(colours.iter())
+            .map(|(adjective, (colour, fg))| {
+                (format!("{} {} potion", adjective, colour), RGB::named(*fg))
+            })
+            .collect();
+        Identification {
+            potion_looks,
+            ..Default::default()
+        }
+    }
+
+    //the first of a kind to turn up decides what every one of them looks like
+    pub fn disguise(&mut self, name: &str, class: MagicItemClass, rng: &mut RandomNumberGenerator) {
+        if self.appearances.contains_key(name) {
+            return;
+        }
+        let appearance = match class {
+            MagicItemClass::Potion => {
+                let look = self.potion_looks[self.potions_seen % self.potion_looks.len()].clone();
+                self.potions_seen += 1;
+                look
+            }
+            //scrolls get a made up title of two nonsense words
+            MagicItemClass::Scroll => {
+                let mut title = format!("scroll of {}", scroll_title(rng));
+                while self
+                    .appearances
+                    .values()
+                    .any(|(taken, _fg)| *taken == title)
+                {
+                    title = format!("scroll of {}", scroll_title(rng));
+                }
+                (title, RGB::named(SCROLL_COLOUR))
+            }
+        };
+        self.appearances.insert(name.to_string(), appearance);
+    }
+
+    pub fn is_identified(&self, name: &str) -> bool {
+        !self.appearances.contains_key(name) || self.identified.contains(name)
+    }
+
+    //returns true the first time a kind of item is learned
+    pub fn identify(&mut self, name: &str) -> bool {
+        !self.is_identified(name) && self.identified.insert(name.to_string())
+    }
+
+    //the name the player knows this kind of item by
+    pub fn display_name(&self, name: &str) -> String {
+        match self.appearances.get(name) {
+            Some((appearance, _fg)) if !self.identified.contains(name) => appearance.clone(),
+            _ => name.to_string(),
+        }
+    }
+
+    //the glyph colour to draw this kind of item in, so it doesn't give the game away
+    pub fn display_fg(&self, name: &str, fg: RGB) -> RGB {
+        match self.appearances.get(name) {
+            Some((_appearance, appearance_fg)) if !self.identified.contains(name) => *appearance_fg,
+            _ => fg,
+        }
+    }
+}
+
+fn scroll_title(rng: &mut RandomNumberGenerator) -> String {
+    let mut words: Vec<String> = Vec::new();
+    for _ in 0..2 {
+        let mut word = String::new();
+        for _ in 0..rng.roll_dice(1, 2) {
+            word += SCROLL_SYLLABLES[rng.roll_dice(1, SCROLL_SYLLABLES.len() as i32) as usize - 1];
+        }
+        words.push(word);
+    }
+    words.join(" ")
+}
+
+fn shuffle<T>(list: &mut [T], rng: &mut RandomNumberGenerator) {
+    for i in (1..list.len()).rev() {
+        let j = rng.roll_dice(1, i as i32 + 1) as usize - 1;
+        list.swap(i, j);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fresh() -> (Identification, RandomNumberGenerator) {
+        let mut rng = RandomNumberGenerator::seeded(7);
+        (Identification::new(&mut rng), rng)
+    }
+
+    #[test]
+    fn disguising_a_kind_twice_keeps_its_first_look() {
+        let (mut identification, mut rng) = fresh();
+        identification.disguise("Health Potion", MagicItemClass::Potion, &mut rng);
+        let look = identification.display_name("Health Potion");
+
+        identification.disguise("Health Potion", MagicItemClass::Potion, &mut rng);
+        identification.disguise("Haste Potion", MagicItemClass::Potion, &mut rng);
+
+        assert_eq!(identification.display_name("Health Potion"), look);
+        assert_ne!(identification.display_name("Haste Potion"), look);
+    }
+
+    #[test]
+    fn scroll_titles_never_collide() {
+        let (mut identification, mut rng) = fresh();
+        let names: Vec<String> = (0..100).map(|i| format!("Scroll {}", i)).collect();
+        for name in names.iter() {
+            identification.disguise(name, MagicItemClass::Scroll, &mut rng);
+        }
+
+        let titles: HashSet<String> = names
+            .iter()
+            .map(|name| identification.display_name(name))
+            .collect();
+        assert_eq!(titles.len(), names.len());
+    }
+
+    #[test]
+    fn identify_only_reports_the_first_time() {
+        let (mut identification, mut rng) = fresh();
+        identification.disguise("Fireball Scroll", MagicItemClass::Scroll, &mut rng);
+
+        assert!(identification.identify("Fireball Scroll"));
+        assert!(!identification.identify("Fireball Scroll"));
+    }
+
+    #[test]
+    fn identified_kinds_show_their_real_name_and_colour() {
+        let (mut identification, mut rng) = fresh();
+        let fg = RGB::named(rltk::MAGENTA);
+        identification.disguise("Health Potion", MagicItemClass::Potion, &mut rng);
+        assert_ne!(
+            identification.display_name("Health Potion"),
+            "Health Potion"
+        );
+        assert_ne!(identification.display_fg("Health Potion", fg), fg);
+
+        identification.identify("Health Potion");
+
+        assert_eq!(
+            identification.display_name("Health Potion"),
+            "Health Potion"
+        );
+        assert_eq!(identification.display_fg("Health Potion", fg), fg);
+    }
+
+    #[test]
+    fn kinds_without_a_disguise_are_already_known() {
+        let (mut identification, _rng) = fresh();
+
+        assert!(identification.is_identified("Dagger"));
+        assert!(!identification.identify("Dagger"));
+        assert_eq!(identification.display_name("Dagger"), "Dagger");
+    }
+}
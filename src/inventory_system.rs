@@ -1,40 +1,119 @@
+use crate::combat::{carried_weight, carry_capacity};
 use crate::components::*;
 use crate::gamelog::*;
+use crate::identification::Identification;
 use crate::map::*;
+use crate::particle_system::ParticleBuilder;
+use rltk::{Point, RGB};
 use specs::prelude::*;
 
+//"Arrow (5)" for a stack of several, just the name otherwise
+pub fn stack_name(name: &str, stack: Option<&Stackable>) -> String {
+    match stack {
+        Some(stack) if stack.quantity > 1 => format!("{} ({})", name, stack.quantity),
+        _ => name.to_string(),
+    }
+}
+
+//"a Dagger", or "Arrow (5)" when there are several
+fn some_of(name: &str, stack: Option<&Stackable>) -> String {
+    match stack {
+        Some(stack) if stack.quantity > 1 => stack_name(name, Some(stack)),
+        _ => format!("a {}", name),
+    }
+}
+
 pub struct ItemCollectionSystem {}
 
 impl<'a> System<'a> for ItemCollectionSystem {
     type SystemData = (
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
+        Entities<'a>,
         WriteStorage<'a, WantsToPickupItem>,
         WriteStorage<'a, Position>,
         ReadStorage<'a, Named>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        WriteStorage<'a, Stackable>,
+        ReadExpect<'a, Identification>,
+        ReadExpect<'a, Map>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack) =
-            data;
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_pickup,
+            mut positions,
+            names,
+            mut backpack,
+            items,
+            equipped,
+            attributes,
+            mut stacks,
+            identification,
+            map,
+        ) = data;
 
         for pickup in wants_pickup.join() {
+            let collector = pickup.collected_by;
+            let capacity = attributes.get(collector).map(carry_capacity);
+            let was_burdened = capacity.is_some_and(|capacity| {
+                carried_weight(collector, &items, &backpack, &equipped, &stacks) > capacity
+            });
+            let item_name = &names.get(pickup.item).unwrap().name;
+            let picked_up = some_of(
+                &identification.display_name(item_name),
+                stacks.get(pickup.item),
+            );
             positions.remove(pickup.item);
-            backpack
-                .insert(
-                    pickup.item,
-                    InBackpack {
-                        owner: pickup.collected_by,
-                    },
-                )
-                .expect("Unable to insert backpack entry");
 
-            if pickup.collected_by == *player_entity {
-                gamelog.entries.push(format!(
-                    "You acquire a {}.",
-                    names.get(pickup.item).unwrap().name
-                ))
+            //a stack of the same thing already carried just gets bigger
+            let existing = stacks.get(pickup.item).and_then(|_| {
+                (&entities, &backpack, &names, &stacks)
+                    .join()
+                    .find(|(entity, pack, name, _stack)| {
+                        *entity != pickup.item && pack.owner == collector && name.name == *item_name
+                    })
+                    .map(|(entity, _pack, _name, _stack)| entity)
+            });
+            match existing {
+                Some(existing) => {
+                    let added = stacks.get(pickup.item).map_or(1, |stack| stack.quantity);
+                    if let Some(stack) = stacks.get_mut(existing) {
+                        stack.quantity += added;
+                    }
+                    entities
+                        .delete(pickup.item)
+                        .expect("unable to delete merged stack");
+                }
+                None => {
+                    backpack
+                        .insert(pickup.item, InBackpack { owner: collector })
+                        .expect("Unable to insert backpack entry");
+                }
+            }
+
+            if collector == *player_entity {
+                gamelog.entries.push(format!("You acquire {}.", picked_up));
+                let burdened = capacity.is_some_and(|capacity| {
+                    carried_weight(collector, &items, &backpack, &equipped, &stacks) > capacity
+                });
+                if burdened && !was_burdened {
+                    gamelog
+                        .entries
+                        .push("You are overburdened, and your aim suffers.".to_string());
+                }
+            } else if let (Some(collector), true) =
+                (names.get(collector), map.player_sees(&positions, collector))
+            {
+                gamelog
+                    .entries
+                    .push(format!("{} picks up {}.", collector.name, picked_up))
             }
         }
         wants_pickup.clear();
@@ -48,14 +127,38 @@ impl<'a> System<'a> for ItemUseSystem {
         ReadExpect<'a, Entity>,
         WriteExpect<'a, GameLog>,
         Entities<'a>,
-        ReadStorage<'a, WantsToUseItem>,
+        WriteStorage<'a, WantsToUseItem>,
         ReadStorage<'a, Named>,
         ReadStorage<'a, Consumable>,
         WriteStorage<'a, CombatStats>,
         ReadStorage<'a, ProvidesHealing>,
         ReadStorage<'a, InflictsDamage>,
+        ReadStorage<'a, AreaOfEffect>,
+        ReadStorage<'a, Ranged>,
+        ReadStorage<'a, Position>,
         ReadExpect<'a, Map>,
         WriteStorage<'a, SufferDamage>,
+        ReadStorage<'a, Equippable>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, AppliesStatus>,
+        WriteStorage<'a, Confused>,
+        WriteStorage<'a, Poisoned>,
+        WriteStorage<'a, Asleep>,
+        WriteStorage<'a, Paralyzed>,
+        WriteStorage<'a, Hasted>,
+        ReadStorage<'a, ProvidesFood>,
+        WriteStorage<'a, HungerClock>,
+        (
+            WriteExpect<'a, ParticleBuilder>,
+            WriteStorage<'a, Stackable>,
+            WriteExpect<'a, Identification>,
+            ReadStorage<'a, ProvidesIdentification>,
+            WriteStorage<'a, WantsToIdentify>,
+            WriteStorage<'a, Cursed>,
+            ReadStorage<'a, RemovesCurse>,
+            ReadStorage<'a, RevealsCurses>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -63,59 +166,396 @@ impl<'a> System<'a> for ItemUseSystem {
             player_entity,
             mut gamelog,
             entities,
-            wants_use,
+            mut wants_use,
             names,
             consumables,
             mut combat_stats,
             healing,
             inflict_damage,
+            aoe,
+            ranged,
+            positions,
             map,
             mut suffer_damage,
+            equippable,
+            mut equipped,
+            mut backpack,
+            applies_status,
+            mut confused,
+            mut poisoned,
+            mut asleep,
+            mut paralyzed,
+            mut hasted,
+            provides_food,
+            mut hunger_clocks,
+            (
+                mut particle_builder,
+                mut stacks,
+                mut identification,
+                provides_identification,
+                mut wants_identify,
+                mut cursed,
+                removes_curse,
+                reveals_curses,
+            ),
         ) = data;
-        for (entity, use_item, stats) in (&entities, &wants_use, &mut combat_stats).join() {
+        for (entity, use_item) in (&entities, &wants_use).join() {
+            let is_player = entity == *player_entity;
+            let seen = is_player || map.player_sees(&positions, entity);
+            let user_name = names
+                .get(entity)
+                .map_or("Something".to_string(), |n| n.name.clone());
+            let true_name = names
+                .get(use_item.item)
+                .map_or("something".to_string(), |n| n.name.clone());
+            let item_name = identification.display_name(&true_name);
+
+            //a targeted item affects whatever can fight on the chosen tile, otherwise the user
+            let mut targets: Vec<Entity> = Vec::new();
+            match use_item.target {
+                None => targets.push(entity),
+                Some(target_point) => {
+                    //ranged effects stop at the first wall or creature in the way, and a wall
+                    //right in front of the user smothers them completely
+                    let mut impact = Some(target_point);
+                    if ranged.get(use_item.item).is_some() {
+                        if let Some(user_pos) = positions.get(entity) {
+                            let path =
+                                map.line_of_fire(Point::new(user_pos.x, user_pos.y), target_point);
+                            impact = path.last().copied();
+                        }
+                    }
+                    let tiles = match (impact, aoe.get(use_item.item)) {
+                        (None, _) => {
+                            if is_player {
+                                gamelog
+                                    .entries
+                                    .push(format!("The {} fizzles against the wall.", item_name));
+                            }
+                            Vec::new()
+                        }
+                        (Some(impact), None) => vec![impact],
+                        (Some(impact), Some(area)) => map.area_of_effect(impact, area.radius),
+                    };
+                    for tile in tiles.iter() {
+                        particle_builder.request(
+                            tile.x,
+                            tile.y,
+                            RGB::named(rltk::ORANGE),
+                            RGB::named(rltk::BLACK),
+                            rltk::to_cp437('░'),
+                            200.0,
+                        );
+                        let idx = map.xy_idx(tile.x, tile.y);
+                        for mob in map.tile_content[idx].iter() {
+                            if combat_stats.get(*mob).is_some() {
+                                targets.push(*mob);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(can_equip) = equippable.get(use_item.item) {
+                let target_slot = can_equip.slot;
+
+                let mut to_unequip: Vec<Entity> = Vec::new();
+                for (item_entity, already_equipped) in (&entities, &equipped).join() {
+                    if already_equipped.owner == entity && already_equipped.slot == target_slot {
+                        to_unequip.push(item_entity);
+                    }
+                }
+                //whatever is stuck in the slot stays there
+                if let Some(stuck) = to_unequip.iter().find(|item| cursed.get(**item).is_some()) {
+                    let stuck = *stuck;
+                    if is_player {
+                        if let Some(curse) = cursed.get_mut(stuck) {
+                            curse.revealed = true;
+                        }
+                        gamelog.entries.push(format!(
+                            "You can't take off the {}, it's cursed!",
+                            names.get(stuck).unwrap().name
+                        ));
+                    }
+                    continue;
+                }
+                for item in to_unequip.iter() {
+                    equipped.remove(*item);
+                    backpack
+                        .insert(*item, InBackpack { owner: entity })
+                        .expect("unable to insert backpack entry");
+                    let unequipped = names.get(*item).unwrap();
+                    if is_player {
+                        gamelog
+                            .entries
+                            .push(format!("You unequip {}.", unequipped.name));
+                    } else if seen {
+                        gamelog
+                            .entries
+                            .push(format!("{} unequips {}.", user_name, unequipped.name));
+                    }
+                }
+
+                equipped
+                    .insert(
+                        use_item.item,
+                        Equipped {
+                            owner: entity,
+                            slot: target_slot,
+                        },
+                    )
+                    .expect("unable to insert equipped component");
+                backpack.remove(use_item.item);
+                if is_player {
+                    gamelog.entries.push(format!("You equip {}.", item_name));
+                    if let Some(curse) = cursed.get_mut(use_item.item) {
+                        curse.revealed = true;
+                        gamelog
+                            .entries
+                            .push(format!("The {} grips you tightly. It's cursed!", item_name));
+                    }
+                } else if seen {
+                    gamelog
+                        .entries
+                        .push(format!("{} equips {}.", user_name, item_name));
+                }
+            }
+
+            //using one from a stack leaves the rest
             let consumable = consumables.get(use_item.item);
             match consumable {
                 None => {}
-                Some(_) => {
-                    entities.delete(use_item.item).expect("item  delete failed");
-                }
+                Some(_) => match stacks.get_mut(use_item.item) {
+                    Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+                    _ => entities.delete(use_item.item).expect("item  delete failed"),
+                },
             }
             let item_heals = healing.get(use_item.item);
             match item_heals {
                 None => {}
                 Some(healer) => {
-                    stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
-                    if entity == *player_entity {
+                    if let Some(stats) = combat_stats.get_mut(entity) {
+                        stats.hp = i32::min(stats.max_hp, stats.hp + healer.heal_amount);
+                    }
+                    if let Some(pos) = positions.get(entity) {
+                        particle_builder.request(
+                            pos.x,
+                            pos.y,
+                            RGB::named(rltk::GREEN),
+                            RGB::named(rltk::BLACK),
+                            rltk::to_cp437('♥'),
+                            200.0,
+                        );
+                    }
+                    if is_player {
                         gamelog.entries.push(format!(
                             "You drink {}, healing {} HP!",
-                            names.get(use_item.item).unwrap().name,
-                            healer.heal_amount
+                            item_name, healer.heal_amount
+                        ));
+                    } else if seen {
+                        gamelog.entries.push(format!(
+                            "{} drinks {}, healing {} HP!",
+                            user_name, item_name, healer.heal_amount
                         ));
                     }
                 }
             }
+            if provides_food.get(use_item.item).is_some() {
+                if let Some(hunger) = hunger_clocks.get_mut(entity) {
+                    hunger.state = HungerState::WellFed;
+                    hunger.duration = 20;
+                }
+                if is_player {
+                    gamelog.entries.push(format!("You eat the {}.", item_name));
+                } else if seen {
+                    gamelog
+                        .entries
+                        .push(format!("{} eats the {}.", user_name, item_name));
+                }
+            }
             let item_damages = inflict_damage.get(use_item.item);
             match item_damages {
                 None => {}
                 Some(damage) => {
-                    let target_point = use_item.target.unwrap();
-                    let idx = map.xy_idx(target_point.x, target_point.y);
-                    //used_item = false
-                    for mob in map.tile_content[idx].iter() {
-                        SufferDamage::new_damage(&mut suffer_damage, *mob, damage.damage);
-                        if entity == *player_entity {
-                            let mob_name = names.get(*mob).unwrap();
-                            let item_name = names.get(use_item.item).unwrap();
+                    for mob in targets.iter() {
+                        SufferDamage::new_damage(
+                            &mut suffer_damage,
+                            *mob,
+                            damage.damage,
+                            damage.damage_type,
+                            Some(entity),
+                        );
+                        let mob_name = names.get(*mob).unwrap();
+                        if is_player {
                             gamelog.entries.push(format!(
                                 "You use {} on {}, dealing {} damage!",
-                                item_name.name, mob_name.name, damage.damage
+                                item_name, mob_name.name, damage.damage
+                            ));
+                        } else if seen || *mob == *player_entity {
+                            gamelog.entries.push(format!(
+                                "{} uses {} on {}, dealing {} damage!",
+                                user_name, item_name, mob_name.name, damage.damage
+                            ));
+                        }
+                    }
+                }
+            }
+
+            if let Some(status) = applies_status.get(use_item.item) {
+                for target in targets.iter() {
+                    match status.effect {
+                        StatusEffect::Confusion => {
+                            confused
+                                .insert(
+                                    *target,
+                                    Confused {
+                                        turns: status.turns,
+                                    },
+                                )
+                                .expect("unable to insert confusion");
+                        }
+                        StatusEffect::Poison { damage } => {
+                            poisoned
+                                .insert(
+                                    *target,
+                                    Poisoned {
+                                        turns: status.turns,
+                                        damage,
+                                        source: Some(entity),
+                                    },
+                                )
+                                .expect("unable to insert poison");
+                        }
+                        StatusEffect::Sleep => {
+                            asleep
+                                .insert(
+                                    *target,
+                                    Asleep {
+                                        turns: status.turns,
+                                    },
+                                )
+                                .expect("unable to insert sleep");
+                        }
+                        StatusEffect::Paralysis => {
+                            paralyzed
+                                .insert(
+                                    *target,
+                                    Paralyzed {
+                                        turns: status.turns,
+                                    },
+                                )
+                                .expect("unable to insert paralysis");
+                        }
+                        StatusEffect::Haste => {
+                            hasted
+                                .insert(
+                                    *target,
+                                    Hasted {
+                                        turns: status.turns,
+                                        free_action: false,
+                                    },
+                                )
+                                .expect("unable to insert haste");
+                        }
+                    }
+                    if *target == *player_entity {
+                        gamelog
+                            .entries
+                            .push(format!("You are {}!", status.effect.adjective()));
+                    } else if map.player_sees(&positions, *target) {
+                        gamelog.entries.push(format!(
+                            "{} is {}!",
+                            names.get(*target).unwrap().name,
+                            status.effect.adjective()
+                        ));
+                    }
+                }
+            }
+
+            //without a chosen target the scroll picks something unknown from the pack itself
+            if is_player && provides_identification.get(use_item.item).is_some() {
+                let target = wants_identify.get(entity).map(|t| t.item).or_else(|| {
+                    (&entities, &backpack, &names)
+                        .join()
+                        .find(|(_item, pack, name)| {
+                            pack.owner == entity
+                                && name.name != true_name
+                                && !identification.is_identified(&name.name)
+                        })
+                        .map(|(item, _pack, _name)| item)
+                });
+                if let Some(target) = target {
+                    if let Some(target_name) = names.get(target) {
+                        let appearance = identification.display_name(&target_name.name);
+                        if identification.identify(&target_name.name) {
+                            gamelog.entries.push(format!(
+                                "You identify the {}: {}.",
+                                appearance, target_name.name
                             ));
                         }
-                        //used_item = true;
                     }
                 }
             }
+
+            //curse scrolls work on everything the reader carries or wears
+            if removes_curse.get(use_item.item).is_some()
+                || reveals_curses.get(use_item.item).is_some()
+            {
+                let mut carried: Vec<Entity> = (&entities, &backpack)
+                    .join()
+                    .filter(|(_item, pack)| pack.owner == entity)
+                    .map(|(item, _pack)| item)
+                    .collect();
+                carried.extend(
+                    (&entities, &equipped)
+                        .join()
+                        .filter(|(_item, equipped_by)| equipped_by.owner == entity)
+                        .map(|(item, _equipped_by)| item),
+                );
+                let afflicted: Vec<Entity> = carried
+                    .into_iter()
+                    .filter(|item| cursed.get(*item).is_some())
+                    .collect();
+                if removes_curse.get(use_item.item).is_some() {
+                    for item in afflicted.iter() {
+                        cursed.remove(*item);
+                    }
+                    if is_player && afflicted.is_empty() {
+                        gamelog
+                            .entries
+                            .push("You feel a brief warmth, but nothing changes.".to_string());
+                    } else if is_player {
+                        gamelog
+                            .entries
+                            .push("You feel as if someone is watching over you.".to_string());
+                    }
+                } else if is_player {
+                    for item in afflicted.iter() {
+                        if let Some(curse) = cursed.get_mut(*item) {
+                            curse.revealed = true;
+                        }
+                        gamelog.entries.push(format!(
+                            "The {} glows with a dark light.",
+                            identification.display_name(&names.get(*item).unwrap().name)
+                        ));
+                    }
+                    if afflicted.is_empty() {
+                        gamelog
+                            .entries
+                            .push("You sense no curses on your belongings.".to_string());
+                    }
+                }
+            }
+
+            //trying something out is the surest way to learn what it is
+            if is_player && identification.identify(&true_name) {
+                gamelog
+                    .entries
+                    .push(format!("You identify the {}: {}.", item_name, true_name));
+            }
         }
+        wants_use.clear();
+        wants_identify.clear();
     }
 }
 
@@ -130,6 +570,8 @@ impl<'a> System<'a> for ItemDropSystem {
         ReadStorage<'a, Named>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, InBackpack>,
+        ReadStorage<'a, Stackable>,
+        ReadExpect<'a, Identification>,
     );
     fn run(&mut self, data: Self::SystemData) {
         let (
@@ -140,6 +582,8 @@ impl<'a> System<'a> for ItemDropSystem {
             names,
             mut positions,
             mut backpack,
+            stacks,
+            identification,
         ) = data;
 
         for (entity, to_drop) in (&entities, &wants_drop).join() {
@@ -163,10 +607,174 @@ impl<'a> System<'a> for ItemDropSystem {
             if entity == *player_entity {
                 gamelog.entries.push(format!(
                     "You drop {}.",
-                    names.get(to_drop.item).unwrap().name
+                    stack_name(
+                        &identification.display_name(&names.get(to_drop.item).unwrap().name),
+                        stacks.get(to_drop.item)
+                    )
                 ));
             }
         }
         wants_drop.clear();
     }
 }
+
+pub struct ItemRemoveSystem {}
+
+impl<'a> System<'a> for ItemRemoveSystem {
+    type SystemData = (
+        ReadExpect<'a, Entity>,
+        WriteExpect<'a, GameLog>,
+        Entities<'a>,
+        WriteStorage<'a, WantsToRemoveItem>,
+        ReadStorage<'a, Named>,
+        WriteStorage<'a, Equipped>,
+        WriteStorage<'a, InBackpack>,
+        WriteStorage<'a, Cursed>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            player_entity,
+            mut gamelog,
+            entities,
+            mut wants_remove,
+            names,
+            mut equipped,
+            mut backpack,
+            mut cursed,
+        ) = data;
+
+        for (entity, to_remove) in (&entities, &wants_remove).join() {
+            if let Some(curse) = cursed.get_mut(to_remove.item) {
+                if entity == *player_entity {
+                    curse.revealed = true;
+                    gamelog.entries.push(format!(
+                        "You can't take off the {}, it's cursed!",
+                        names.get(to_remove.item).unwrap().name
+                    ));
+                }
+                continue;
+            }
+            equipped.remove(to_remove.item);
+            backpack
+                .insert(to_remove.item, InBackpack { owner: entity })
+                .expect("unable to insert backpack entry");
+
+            if entity == *player_entity {
+                gamelog.entries.push(format!(
+                    "You unequip {}.",
+                    names.get(to_remove.item).unwrap().name
+                ));
+            }
+        }
+        wants_remove.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::open_map;
+
+    //the player in an open room, with storage for everything the system touches
+    fn world_for<S: for<'a> System<'a>>(system: &mut S) -> (World, Entity) {
+        let mut ecs = World::new();
+        system.setup(&mut ecs);
+        ecs.register::<Item>();
+        let player = ecs.create_entity().build();
+        ecs.insert(player);
+        ecs.insert(GameLog {
+            entries: Vec::new(),
+        });
+        ecs.insert(Identification::default());
+        ecs.insert(ParticleBuilder::default());
+        ecs.insert(open_map());
+        (ecs, player)
+    }
+
+    fn stack_of(ecs: &mut World, name: &str, quantity: i32) -> Entity {
+        ecs.create_entity()
+            .with(Named {
+                name: name.to_string(),
+            })
+            .with(Item {
+                weight: 1.0,
+                value: 2,
+            })
+            .with(Stackable { quantity })
+            .build()
+    }
+
+    #[test]
+    fn picking_up_a_matching_stack_merges_into_it() {
+        let mut system = ItemCollectionSystem {};
+        let (mut ecs, player) = world_for(&mut system);
+        let carried = stack_of(&mut ecs, "Arrow", 3);
+        ecs.write_storage::<InBackpack>()
+            .insert(carried, InBackpack { owner: player })
+            .unwrap();
+        let found = stack_of(&mut ecs, "Arrow", 2);
+        ecs.write_storage::<Position>()
+            .insert(found, Position { x: 5, y: 5 })
+            .unwrap();
+        ecs.write_storage::<WantsToPickupItem>()
+            .insert(
+                player,
+                WantsToPickupItem {
+                    collected_by: player,
+                    item: found,
+                },
+            )
+            .unwrap();
+
+        system.run_now(&ecs);
+        ecs.maintain();
+
+        assert_eq!(
+            ecs.read_storage::<Stackable>()
+                .get(carried)
+                .unwrap()
+                .quantity,
+            5
+        );
+        assert!(!ecs.is_alive(found));
+    }
+
+    #[test]
+    fn using_the_last_of_a_stack_deletes_it() {
+        let mut system = ItemUseSystem {};
+        let (mut ecs, player) = world_for(&mut system);
+        let rations = stack_of(&mut ecs, "Rations", 2);
+        ecs.write_storage::<Consumable>()
+            .insert(rations, Consumable {})
+            .unwrap();
+        ecs.write_storage::<InBackpack>()
+            .insert(rations, InBackpack { owner: player })
+            .unwrap();
+        let mut eat = |ecs: &mut World| {
+            ecs.write_storage::<WantsToUseItem>()
+                .insert(
+                    player,
+                    WantsToUseItem {
+                        item: rations,
+                        target: None,
+                    },
+                )
+                .unwrap();
+            system.run_now(ecs);
+            ecs.maintain();
+        };
+
+        eat(&mut ecs);
+        assert_eq!(
+            ecs.read_storage::<Stackable>()
+                .get(rations)
+                .unwrap()
+                .quantity,
+            1
+        );
+
+        eat(&mut ecs);
+        assert!(!ecs.is_alive(rations));
+    }
+}
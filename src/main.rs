@@ -14,18 +14,37 @@ mod rect;
 mod visibility_system;
 use visibility_system::*;
 mod damage_system;
+mod faction;
 use damage_system::*;
 mod map_indexing_system;
 use map_indexing_system::*;
+mod combat;
 mod melee_combat_system;
 use melee_combat_system::*;
+mod ranged_combat_system;
+use ranged_combat_system::*;
+mod spell_system;
+use spell_system::*;
 mod gui;
 use gui::*;
 mod gamelog;
+mod hunger_system;
+mod identification;
+use hunger_system::*;
+use identification::Identification;
+mod scent_system;
+use scent_system::*;
+mod particle_system;
+use particle_system::*;
+mod regen_system;
+use regen_system::*;
+mod status_effect_system;
 use crate::inventory_system::*;
 use gamelog::*;
+use status_effect_system::*;
 
 mod inventory_system;
+mod random_table;
 mod spawner; //use spawner::*;
 
 #[derive(PartialEq, Copy, Clone)]
@@ -34,9 +53,15 @@ pub enum RunState {
     PreRun,
     PlayerTurn,
     MonsterTurn,
+    HastedMonsterTurn,
     ShowInventory,
     ShowDropItem,
+    ShowDropQuantity { item: Entity, quantity: i32 },
+    ShowIdentify { scroll: Entity },
+    ShowRemoveItem,
     ShowTargeting { range: i32, item: Entity },
+    ShowFireTargeting { range: i32, weapon: Entity },
+    LevelUp,
 }
 
 pub struct State {
@@ -44,6 +69,14 @@ pub struct State {
 }
 impl State {
     pub fn run_systems(&mut self) {
+        let mut status = StatusEffectSystem {};
+        status.run_now(&self.ecs);
+        let mut hunger = HungerSystem {};
+        hunger.run_now(&self.ecs);
+        let mut regen = RegenerationSystem {};
+        regen.run_now(&self.ecs);
+        let mut scent = ScentSystem {};
+        scent.run_now(&self.ecs);
         let mut vis = VisibilitySystem {};
         vis.run_now(&self.ecs);
         let mut mobs = MonsterAISystem {};
@@ -52,6 +85,10 @@ impl State {
         map_index.run_now(&self.ecs);
         let mut melee = MeleeCombatSystem {};
         melee.run_now(&self.ecs);
+        let mut ranged = RangedCombatSystem {};
+        ranged.run_now(&self.ecs);
+        let mut spells = SpellSystem {};
+        spells.run_now(&self.ecs);
         let mut damage = DamageSystem {};
         damage.run_now(&self.ecs);
         let mut pickup = ItemCollectionSystem {};
@@ -60,25 +97,34 @@ impl State {
         item_use.run_now(&self.ecs);
         let mut drop_sys = ItemDropSystem {};
         drop_sys.run_now(&self.ecs);
+        let mut remove_sys = ItemRemoveSystem {};
+        remove_sys.run_now(&self.ecs);
         self.ecs.maintain();
     }
 }
 impl GameState for State {
     fn tick(&mut self, ctx: &mut Rltk) {
         ctx.cls();
+        cull_dead_particles(&mut self.ecs, ctx);
         draw_map(&self.ecs, ctx);
 
         {
             let positions = self.ecs.read_storage::<Position>();
             let renderables = self.ecs.read_storage::<Renderable>();
+            let names = self.ecs.read_storage::<Named>();
+            let identification = self.ecs.fetch::<Identification>();
             let map = self.ecs.fetch::<Map>();
 
-            let mut data = (&positions, &renderables).join().collect::<Vec<_>>();
-            data.sort_by(|&a, &b| b.1.render_order.cmp(&a.1.render_order));
-            for (pos, render) in data.iter() {
+            let mut data = (&positions, &renderables, names.maybe())
+                .join()
+                .collect::<Vec<_>>();
+            data.sort_by_key(|&a| std::cmp::Reverse(a.1.render_order));
+            for (pos, render, name) in data.iter() {
                 let idx = map.xy_idx(pos.x, pos.y);
                 if map.visible_tiles[idx] {
-                    ctx.set(pos.x, pos.y, render.fg, render.bg, render.glyph)
+                    let fg =
+                        name.map_or(render.fg, |n| identification.display_fg(&n.name, render.fg));
+                    ctx.set(pos.x, pos.y, fg, render.bg, render.glyph)
                 }
             }
             draw_ui(&self.ecs, ctx);
@@ -96,14 +142,31 @@ impl GameState for State {
                 new_run_state = RunState::AwaitingInput;
             }
             RunState::AwaitingInput => {
-                new_run_state = player_input(self, ctx);
+                if player_has_level_up(&self.ecs) {
+                    new_run_state = RunState::LevelUp;
+                } else {
+                    new_run_state = player_input(self, ctx);
+                }
             }
             RunState::PlayerTurn => {
                 self.run_systems();
                 self.ecs.maintain();
-                new_run_state = RunState::MonsterTurn;
+                if player_has_free_action(&mut self.ecs) {
+                    new_run_state = RunState::AwaitingInput;
+                } else {
+                    new_run_state = RunState::MonsterTurn;
+                }
             }
             RunState::MonsterTurn => {
+                self.run_systems();
+                self.ecs.maintain();
+                if monster_has_haste(&self.ecs) {
+                    new_run_state = RunState::HastedMonsterTurn;
+                } else {
+                    new_run_state = RunState::AwaitingInput;
+                }
+            }
+            RunState::HastedMonsterTurn => {
                 self.run_systems();
                 self.ecs.maintain();
                 new_run_state = RunState::AwaitingInput;
@@ -118,11 +181,37 @@ impl GameState for State {
                         let item_entity = result.1.unwrap();
                         let is_ranged = self.ecs.read_storage::<Ranged>();
                         let is_item_ranged = is_ranged.get(item_entity);
+                        //a known identify scroll lets you choose what to read it on
+                        let chooses_identify = self
+                            .ecs
+                            .read_storage::<ProvidesIdentification>()
+                            .get(item_entity)
+                            .is_some()
+                            && self
+                                .ecs
+                                .read_storage::<Named>()
+                                .get(item_entity)
+                                .is_some_and(|name| {
+                                    self.ecs.fetch::<Identification>().is_identified(&name.name)
+                                });
                         if let Some(is_item_ranged) = is_item_ranged {
                             new_run_state = RunState::ShowTargeting {
                                 range: is_item_ranged.range,
                                 item: item_entity,
                             };
+                        } else if chooses_identify
+                            && unidentified_items(&self.ecs, item_entity).is_empty()
+                        {
+                            //no sense wasting the scroll
+                            self.ecs
+                                .fetch_mut::<GameLog>()
+                                .entries
+                                .push("You have nothing to identify.".to_string());
+                            new_run_state = RunState::AwaitingInput;
+                        } else if chooses_identify {
+                            new_run_state = RunState::ShowIdentify {
+                                scroll: item_entity,
+                            };
                         } else {
                             let mut intent = self.ecs.write_storage::<WantsToUseItem>();
                             intent
@@ -146,20 +235,105 @@ impl GameState for State {
                     ItemMenuResult::NoResponse => {}
                     ItemMenuResult::Selected => {
                         let item_entity = result.1.unwrap();
+                        let quantity = self
+                            .ecs
+                            .read_storage::<Stackable>()
+                            .get(item_entity)
+                            .map_or(1, |stack| stack.quantity);
+                        if quantity > 1 {
+                            new_run_state = RunState::ShowDropQuantity {
+                                item: item_entity,
+                                quantity,
+                            };
+                        } else {
+                            let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                            intent
+                                .insert(
+                                    *self.ecs.fetch::<Entity>(),
+                                    WantsToDropItem { item: item_entity },
+                                )
+                                .expect("unable to insert intent, drop system");
+                            new_run_state = RunState::PlayerTurn;
+                        }
+                    }
+                }
+            }
+            RunState::ShowIdentify { scroll } => {
+                let result = identify_menu(self, ctx, scroll);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        self.ecs
+                            .write_storage::<WantsToIdentify>()
+                            .insert(
+                                player_entity,
+                                WantsToIdentify {
+                                    item: result.1.unwrap(),
+                                },
+                            )
+                            .expect("unable to insert intent");
+                        self.ecs
+                            .write_storage::<WantsToUseItem>()
+                            .insert(
+                                player_entity,
+                                WantsToUseItem {
+                                    item: scroll,
+                                    target: None,
+                                },
+                            )
+                            .expect("unable to insert intent");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::ShowDropQuantity { item, quantity } => {
+                let result = drop_quantity_menu(self, ctx, item, quantity);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {
+                        new_run_state = RunState::ShowDropQuantity {
+                            item,
+                            quantity: result.1,
+                        }
+                    }
+                    ItemMenuResult::Selected => {
+                        //dropping part of a stack splits it first
+                        let to_drop =
+                            spawner::split_stack(&mut self.ecs, item, result.1).unwrap_or(item);
                         let mut intent = self.ecs.write_storage::<WantsToDropItem>();
                         intent
                             .insert(
                                 *self.ecs.fetch::<Entity>(),
-                                WantsToDropItem { item: item_entity },
+                                WantsToDropItem { item: to_drop },
                             )
                             .expect("unable to insert intent, drop system");
                         new_run_state = RunState::PlayerTurn;
                     }
                 }
             }
+            RunState::ShowRemoveItem => {
+                let result = remove_item_menu(self, ctx);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let item_entity = result.1.unwrap();
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToRemoveItem { item: item_entity },
+                            )
+                            .expect("unable to insert intent, remove system");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
 
             RunState::ShowTargeting { range, item } => {
-                let result = ranged_target(self, ctx, range);
+                let result = ranged_target(self, ctx, range, item);
                 match result.0 {
                     ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
                     ItemMenuResult::NoResponse => {}
@@ -178,12 +352,42 @@ impl GameState for State {
                     }
                 }
             }
+            RunState::ShowFireTargeting { range, weapon } => {
+                let result = ranged_target(self, ctx, range, weapon);
+                match result.0 {
+                    ItemMenuResult::Cancel => new_run_state = RunState::AwaitingInput,
+                    ItemMenuResult::NoResponse => {}
+                    ItemMenuResult::Selected => {
+                        let mut intent = self.ecs.write_storage::<WantsToShoot>();
+                        intent
+                            .insert(
+                                *self.ecs.fetch::<Entity>(),
+                                WantsToShoot {
+                                    target: result.1.unwrap(),
+                                },
+                            )
+                            .expect("unable to insert intent, fire targeting");
+                        new_run_state = RunState::PlayerTurn;
+                    }
+                }
+            }
+            RunState::LevelUp => {
+                let result = level_up_menu(self, ctx);
+                if let (ItemMenuResult::Selected, Some(choice)) = result {
+                    apply_level_up(&mut self.ecs, choice);
+                    new_run_state = RunState::AwaitingInput;
+                }
+            }
         }
         {
             let mut run_writer = self.ecs.write_resource::<RunState>();
             *run_writer = new_run_state;
         }
         delete_the_dead(&mut self.ecs);
+        spawner::summon_monsters(&mut self.ecs);
+        //spawned every frame so effects from deaths show up without waiting for a turn
+        let mut particles = ParticleSpawnSystem {};
+        particles.run_now(&self.ecs);
     }
 }
 
@@ -200,14 +404,38 @@ fn main() -> rltk::BError {
     gs.ecs.register::<Player>();
     gs.ecs.register::<Viewshed>();
     gs.ecs.register::<Monster>();
+    gs.ecs.register::<MonsterMemory>();
+    gs.ecs.register::<Faction>();
+    gs.ecs.register::<Surrendered>();
+    gs.ecs.register::<Ally>();
+    gs.ecs.register::<Unaware>();
+    gs.ecs.register::<Morale>();
+    gs.ecs.register::<Abilities>();
+    gs.ecs.register::<WantsToCastSpell>();
+    gs.ecs.register::<WantsToSummon>();
+    gs.ecs.register::<GroupMember>();
+    gs.ecs.register::<GroupLeader>();
+    gs.ecs.register::<Unique>();
     gs.ecs.register::<Named>();
     gs.ecs.register::<BlocksTile>();
     gs.ecs.register::<CombatStats>();
+    gs.ecs.register::<Attributes>();
+    gs.ecs.register::<Experience>();
+    gs.ecs.register::<NaturalAttackDefense>();
     gs.ecs.register::<WantsToMelee>();
     gs.ecs.register::<SufferDamage>();
+    gs.ecs.register::<Resistances>();
+    gs.ecs.register::<Immunities>();
+    gs.ecs.register::<Vulnerabilities>();
     gs.ecs.register::<Item>();
-    gs.ecs.register::<Potion>();
     gs.ecs.register::<InBackpack>();
+    gs.ecs.register::<Stackable>();
+    gs.ecs.register::<ProvidesIdentification>();
+    gs.ecs.register::<MagicItem>();
+    gs.ecs.register::<WantsToIdentify>();
+    gs.ecs.register::<Cursed>();
+    gs.ecs.register::<RemovesCurse>();
+    gs.ecs.register::<RevealsCurses>();
     gs.ecs.register::<WantsToPickupItem>();
     gs.ecs.register::<WantsToUseItem>();
     gs.ecs.register::<WantsToDropItem>();
@@ -215,15 +443,46 @@ fn main() -> rltk::BError {
     gs.ecs.register::<ProvidesHealing>();
     gs.ecs.register::<Ranged>();
     gs.ecs.register::<InflictsDamage>();
+    gs.ecs.register::<AreaOfEffect>();
+    gs.ecs.register::<LootTable>();
+    gs.ecs.register::<Equippable>();
+    gs.ecs.register::<Equipped>();
+    gs.ecs.register::<MeleeWeapon>();
+    gs.ecs.register::<RangedWeapon>();
+    gs.ecs.register::<Ammunition>();
+    gs.ecs.register::<WantsToShoot>();
+    gs.ecs.register::<MeleePowerBonus>();
+    gs.ecs.register::<DefenseBonus>();
+    gs.ecs.register::<WantsToRemoveItem>();
+    gs.ecs.register::<AppliesStatus>();
+    gs.ecs.register::<Confused>();
+    gs.ecs.register::<Poisoned>();
+    gs.ecs.register::<Asleep>();
+    gs.ecs.register::<Paralyzed>();
+    gs.ecs.register::<Hasted>();
+    gs.ecs.register::<HungerClock>();
+    gs.ecs.register::<ProvidesFood>();
+    gs.ecs.register::<Regenerates>();
+    gs.ecs.register::<Resting>();
+    gs.ecs.register::<ParticleLifetime>();
 
-    let map: Map = Map::new_map_rooms_and_corridors();
+    let map: Map = Map::new_map_rooms_and_corridors(1);
     let (player_x, player_y) = map.rooms[0].center();
     let player_entity = spawner::player(&mut gs.ecs, player_x, player_y);
+    spawner::dog(&mut gs.ecs, player_x + 1, player_y);
 
     gs.ecs.insert(rltk::RandomNumberGenerator::new());
+    gs.ecs.insert(spawner::GroupIds { next: 0 });
+    gs.ecs.insert(spawner::UniqueRegistry::default());
+    let identification =
+        Identification::new(&mut gs.ecs.write_resource::<rltk::RandomNumberGenerator>());
+    gs.ecs.insert(identification);
+    gs.ecs.insert(ParticleBuilder::default());
+    gs.ecs.insert(Noises::default());
     for room in map.rooms.iter().skip(1) {
         spawner::spawn_room(&mut gs.ecs, room);
     }
+    spawner::spawn_uniques(&mut gs.ecs, &map);
     //resources
     gs.ecs.insert(map);
     gs.ecs.insert(player_entity);
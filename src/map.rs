@@ -1,7 +1,10 @@
 use crate::rect::*;
+use crate::Position;
 use rltk::{Algorithm2D, BaseMap, Point, RandomNumberGenerator, Rltk, SmallVec, RGB};
 use specs::prelude::*;
+use specs::storage::MaskedStorage;
 use std::cmp::{max, min};
+use std::ops::Deref;
 
 pub const MAP_WIDTH: usize = 80;
 pub const MAP_HEIGHT: usize = 43;
@@ -22,6 +25,8 @@ pub struct Map {
     pub visible_tiles: Vec<bool>,
     pub blocked: Vec<bool>,
     pub tile_content: Vec<Vec<Entity>>,
+    pub scent: Vec<i32>,
+    pub depth: i32,
 }
 
 impl Map {
@@ -29,6 +34,29 @@ impl Map {
         (y as usize * 80) + x as usize
     }
 
+    //whether the player can see what happens on this tile right now
+    pub fn in_player_view(&self, x: i32, y: i32) -> bool {
+        self.visible_tiles[self.xy_idx(x, y)]
+    }
+
+    //whether the player can see where this entity stands right now
+    pub fn player_sees<D>(&self, positions: &Storage<Position, D>, entity: Entity) -> bool
+    where
+        D: Deref<Target = MaskedStorage<Position>>,
+    {
+        positions
+            .get(entity)
+            .is_some_and(|pos| self.in_player_view(pos.x, pos.y))
+    }
+
+    //a shot or spell is worth reporting if the player can see either end of it
+    pub fn player_sees_either_end(&self, from: Point, path: &[Point]) -> bool {
+        self.in_player_view(from.x, from.y)
+            || path
+                .last()
+                .is_some_and(|impact| self.in_player_view(impact.x, impact.y))
+    }
+
     pub fn apply_room_to_map(&mut self, room: &Rect) {
         for y in room.y1 + 1..=room.y2 {
             for x in room.x1 + 1..=room.x2 {
@@ -70,13 +98,48 @@ impl Map {
         }
     }
 
+    //tiles within radius of the centre that the centre can see, so walls shield the blast
+    pub fn area_of_effect(&self, center: Point, radius: i32) -> Vec<Point> {
+        let mut tiles = rltk::field_of_view(center, radius, self);
+        tiles.retain(|p| {
+            p.x > 0
+                && p.x < self.width - 1
+                && p.y > 0
+                && p.y < self.height - 1
+                && rltk::DistanceAlg::Pythagoras.distance2d(center, *p) <= radius as f32
+        });
+        tiles
+    }
+
+    //projectiles travel along a bresenham line and stop at the first wall or blocking entity
+    pub fn line_of_fire(&self, start: Point, end: Point) -> Vec<Point> {
+        let mut path = Vec::new();
+        for point in rltk::line2d(rltk::LineAlg::Bresenham, start, end)
+            .iter()
+            .skip(1)
+        {
+            if point.x < 0 || point.x >= self.width || point.y < 0 || point.y >= self.height {
+                break;
+            }
+            let idx = self.xy_idx(point.x, point.y);
+            if self.tiles[idx] == TileType::Wall {
+                break;
+            }
+            path.push(*point);
+            if self.blocked[idx] {
+                break;
+            }
+        }
+        path
+    }
+
     pub fn clear_content_index(&mut self) {
         for content in self.tile_content.iter_mut() {
             content.clear();
         }
     }
 
-    pub fn new_map_rooms_and_corridors() -> Map {
+    pub fn new_map_rooms_and_corridors(depth: i32) -> Map {
         let mut map = Map {
             tiles: vec![TileType::Wall; MAP_CELL_COUNT],
             rooms: Vec::new(),
@@ -86,6 +149,8 @@ impl Map {
             visible_tiles: vec![false; MAP_CELL_COUNT],
             blocked: vec![false; MAP_CELL_COUNT],
             tile_content: vec![Vec::new(); MAP_CELL_COUNT],
+            scent: vec![0; MAP_CELL_COUNT],
+            depth,
         };
 
         const MAX_ROOMS: i32 = 30;
@@ -219,3 +284,34 @@ pub fn draw_map(ecs: &World, ctx: &mut Rltk) {
         }
     }
 }
+
+//an open room with walls only round the edge
+#[cfg(test)]
+pub fn open_map() -> Map {
+    let mut map = Map {
+        tiles: vec![TileType::Floor; MAP_CELL_COUNT],
+        rooms: Vec::new(),
+        width: MAP_WIDTH as i32,
+        height: MAP_HEIGHT as i32,
+        revealed_tiles: vec![false; MAP_CELL_COUNT],
+        visible_tiles: vec![false; MAP_CELL_COUNT],
+        blocked: vec![false; MAP_CELL_COUNT],
+        tile_content: vec![Vec::new(); MAP_CELL_COUNT],
+        scent: vec![0; MAP_CELL_COUNT],
+        depth: 1,
+    };
+    for x in 0..map.width {
+        for y in [0, map.height - 1] {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    for y in 0..map.height {
+        for x in [0, map.width - 1] {
+            let idx = map.xy_idx(x, y);
+            map.tiles[idx] = TileType::Wall;
+        }
+    }
+    map.populate_blocked();
+    map
+}
@@ -1,4 +1,4 @@
-use crate::{BlocksTile, Map, Position};
+use crate::{BlocksTile, Map, ParticleLifetime, Position};
 use specs::prelude::*;
 
 pub struct MapIndexingSystem {}
@@ -9,13 +9,15 @@ impl<'a> System<'a> for MapIndexingSystem {
         ReadStorage<'a, Position>,
         ReadStorage<'a, BlocksTile>,
         Entities<'a>,
+        ReadStorage<'a, ParticleLifetime>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (mut map, position, blockers, entities) = data;
+        let (mut map, position, blockers, entities, particles) = data;
 
         map.populate_blocked();
         map.clear_content_index();
-        for (entity, position) in (&entities, &position).join() {
+        //particles are only for show, nothing should find them on the map
+        for (entity, position, _particle) in (&entities, &position, !&particles).join() {
             let idx = map.xy_idx(position.x, position.y);
 
             let _p: Option<&BlocksTile> = blockers.get(entity);
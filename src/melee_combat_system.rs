@@ -1,4 +1,12 @@
-use crate::{CombatStats, GameLog, Named, SufferDamage, WantsToMelee};
+use crate::combat::{armor_class, encumbrance_penalty, roll_damage, roll_to_hit, AttackResult};
+use crate::particle_system::ParticleBuilder;
+use crate::scent_system::Noises;
+use crate::{
+    Attributes, CombatStats, DamageType, DefenseBonus, Equipped, GameLog, InBackpack, Item, Map,
+    MeleePowerBonus, MeleeWeapon, Named, NaturalAttackDefense, Position, Stackable, SufferDamage,
+    Unaware, WantsToMelee,
+};
+use rltk::{Point, RandomNumberGenerator, RGB};
 use specs::prelude::*;
 
 pub struct MeleeCombatSystem {}
@@ -11,29 +19,193 @@ impl<'a> System<'a> for MeleeCombatSystem {
         ReadStorage<'a, CombatStats>,
         WriteStorage<'a, SufferDamage>,
         WriteExpect<'a, GameLog>,
+        ReadStorage<'a, MeleePowerBonus>,
+        ReadStorage<'a, DefenseBonus>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, MeleeWeapon>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        ReadStorage<'a, Position>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, Noises>,
+        WriteStorage<'a, Unaware>,
+        ReadStorage<'a, Item>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Stackable>,
+        ReadExpect<'a, Map>,
+        ReadExpect<'a, Entity>,
     );
     fn run(&mut self, data: Self::SystemData) {
-        let (entities, mut wants_melee, names, combat_stats, mut inflict_damage, mut log) = data;
+        let (
+            entities,
+            mut wants_melee,
+            names,
+            combat_stats,
+            mut inflict_damage,
+            mut log,
+            melee_power_bonuses,
+            defense_bonuses,
+            equipped,
+            melee_weapons,
+            attributes,
+            natural_attacks,
+            mut rng,
+            positions,
+            mut particle_builder,
+            mut noises,
+            mut unaware,
+            items,
+            backpack,
+            stacks,
+            map,
+            player_entity,
+        ) = data;
 
-        for (_entity, wants_melee, name, stats) in
+        for (entity, wants_melee, name, stats) in
             (&entities, &wants_melee, &names, &combat_stats).join()
         {
             if stats.hp > 0 {
                 let target_stats = combat_stats.get(wants_melee.target).unwrap();
                 if target_stats.hp > 0 {
                     let target_name = names.get(wants_melee.target).unwrap();
-                    let damage = i32::max(0, stats.power - target_stats.defense);
-                    if damage == 0 {
-                        log.entries.push(format!(
-                            "{} is unable to damage {}",
-                            &name.name, &target_name.name
-                        ));
-                    } else {
+                    let seen = [entity, wants_melee.target]
+                        .iter()
+                        .any(|e| map.player_sees(&positions, *e));
+                    if let Some(pos) = positions.get(entity) {
+                        noises.make(Point::new(pos.x, pos.y), 10.0);
+                    }
+
+                    //unarmed unless a weapon is wielded or the attacker has natural weapons
+                    let mut attack_name = "fists".to_string();
+                    let mut hit_bonus = 0;
+                    let mut damage_n_dice = 1;
+                    let mut damage_die_type = 4;
+                    let mut damage_bonus = 0;
+                    let mut damage_type = DamageType::Bludgeoning;
+                    let mut wielding = false;
+                    for (item, weapon, equipped_by) in (&entities, &melee_weapons, &equipped).join()
+                    {
+                        if equipped_by.owner == entity {
+                            wielding = true;
+                            if let Some(weapon_name) = names.get(item) {
+                                attack_name = weapon_name.name.clone();
+                            }
+                            hit_bonus = weapon.hit_bonus;
+                            damage_n_dice = weapon.damage_n_dice;
+                            damage_die_type = weapon.damage_die_type;
+                            damage_bonus = weapon.damage_bonus;
+                            damage_type = weapon.damage_type;
+                        }
+                    }
+                    if !wielding {
+                        if let Some(natural) = natural_attacks.get(entity) {
+                            if !natural.attacks.is_empty() {
+                                let choice = if natural.attacks.len() > 1 {
+                                    rng.roll_dice(1, natural.attacks.len() as i32) as usize - 1
+                                } else {
+                                    0
+                                };
+                                let attack = &natural.attacks[choice];
+                                attack_name = attack.name.clone();
+                                hit_bonus = attack.hit_bonus;
+                                damage_n_dice = attack.damage_n_dice;
+                                damage_die_type = attack.damage_die_type;
+                                damage_bonus = attack.damage_bonus;
+                                damage_type = attack.damage_type;
+                            }
+                        }
+                    }
+
+                    let might = attributes.get(entity).map_or(0, |a| a.might.modifier());
+                    hit_bonus -= encumbrance_penalty(
+                        entity,
+                        &attributes,
+                        &items,
+                        &backpack,
+                        &equipped,
+                        &stacks,
+                    );
+                    for (power_bonus, equipped_by) in (&melee_power_bonuses, &equipped).join() {
+                        if equipped_by.owner == entity {
+                            damage_bonus += power_bonus.power;
+                        }
+                    }
+
+                    let armor_class = armor_class(
+                        wants_melee.target,
+                        &natural_attacks,
+                        &attributes,
+                        &defense_bonuses,
+                        &equipped,
+                    );
+                    let (result, attack_roll) =
+                        roll_to_hit(&mut rng, might + hit_bonus, armor_class);
+                    //only the player sneaks up on things; whatever happens, the target knows now
+                    let sneak_attack =
+                        entity == *player_entity && unaware.remove(wants_melee.target).is_some();
+                    if result == AttackResult::Fumble {
+                        if seen {
+                            log.entries.push(format!(
+                                "{} fumbles an attack on {} with {}.",
+                                &name.name, &target_name.name, attack_name
+                            ));
+                        }
+                    } else if result != AttackResult::Miss {
+                        let mut damage = roll_damage(
+                            &mut rng,
+                            result,
+                            damage_n_dice,
+                            damage_die_type,
+                            might + damage_bonus,
+                        );
+                        if sneak_attack {
+                            damage += rng.roll_dice(damage_n_dice, damage_die_type);
+                            if seen {
+                                log.entries.push(format!(
+                                    "{} catches {} unawares!",
+                                    &name.name, &target_name.name
+                                ));
+                            }
+                        }
+                        if seen && result == AttackResult::Critical {
+                            log.entries.push(format!(
+                                "Critical! {} hits {} with {} for {} hp!",
+                                &name.name, &target_name.name, attack_name, damage
+                            ));
+                        } else if seen {
+                            log.entries.push(format!(
+                                "{} hits {} with {} for {} hp! ({} vs AC {})",
+                                &name.name,
+                                &target_name.name,
+                                attack_name,
+                                damage,
+                                attack_roll,
+                                armor_class
+                            ));
+                        }
+                        SufferDamage::new_damage(
+                            &mut inflict_damage,
+                            wants_melee.target,
+                            damage,
+                            damage_type,
+                            Some(entity),
+                        );
+                        if let Some(pos) = positions.get(wants_melee.target) {
+                            particle_builder.request(
+                                pos.x,
+                                pos.y,
+                                RGB::named(rltk::ORANGE),
+                                RGB::named(rltk::BLACK),
+                                rltk::to_cp437('‼'),
+                                200.0,
+                            );
+                        }
+                    } else if seen {
                         log.entries.push(format!(
-                            "{} hits {} for {} hp!",
-                            &name.name, &target_name.name, damage
+                            "{} misses {} with {}. ({} vs AC {})",
+                            &name.name, &target_name.name, attack_name, attack_roll, armor_class
                         ));
-                        SufferDamage::new_damage(&mut inflict_damage, wants_melee.target, damage);
                     }
                 }
             }
@@ -1,66 +1,683 @@
-use crate::{Map, Monster, Position, RunState, Viewshed, WantsToMelee};
-use rltk::Point;
+use crate::combat::{perception_roll, stealth_dc};
+use crate::faction::{self, FactionId, Reaction};
+use crate::scent_system::Noises;
+use crate::{
+    Abilities, AbilityKind, AiState, Ally, Ammunition, Asleep, Attributes, CombatStats, Confused,
+    DefenseBonus, Equipped, Faction, GameLog, GroupLeader, GroupMember, Hasted, InBackpack, Item,
+    Map, Monster, MonsterMemory, Morale, Named, Paralyzed, Position, ProvidesHealing, Ranged,
+    RangedWeapon, RunState, Surrendered, Unaware, Viewshed, WantsToCastSpell, WantsToMelee,
+    WantsToPickupItem, WantsToShoot, WantsToSummon, WantsToUseItem,
+};
+use rltk::{BaseMap, DijkstraMap, Point, RandomNumberGenerator};
 use specs::prelude::*;
+use std::collections::HashMap;
+
+const LEADER_LEASH: f32 = 2.5;
+const ALLY_LEASH: f32 = 3.0;
+const KIN_LEASH: f32 = 3.0;
+const SEARCH_TURNS: i32 = 10;
+const MORALE_BREAK: i32 = 5;
+const FLEE_DEPTH: f32 = 20.0;
+
 pub struct MonsterAISystem {}
 
 impl<'a> System<'a> for MonsterAISystem {
     type SystemData = (
         WriteExpect<'a, Map>,
-        ReadExpect<'a, Point>,
-        ReadExpect<'a, Entity>,
         ReadExpect<'a, RunState>,
         Entities<'a>,
         WriteStorage<'a, Viewshed>,
         ReadStorage<'a, Monster>,
         WriteStorage<'a, Position>,
         WriteStorage<'a, WantsToMelee>,
+        ReadStorage<'a, GroupMember>,
+        ReadStorage<'a, GroupLeader>,
+        ReadStorage<'a, Confused>,
+        WriteStorage<'a, Asleep>,
+        ReadStorage<'a, Paralyzed>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteStorage<'a, MonsterMemory>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Faction>,
+        WriteStorage<'a, Abilities>,
+        WriteStorage<'a, WantsToShoot>,
+        WriteStorage<'a, WantsToCastSpell>,
+        WriteStorage<'a, WantsToSummon>,
+        ReadStorage<'a, Ammunition>,
+        ReadStorage<'a, InBackpack>,
+        (
+            ReadStorage<'a, Item>,
+            ReadStorage<'a, ProvidesHealing>,
+            ReadStorage<'a, Ranged>,
+            ReadStorage<'a, Attributes>,
+            WriteStorage<'a, WantsToPickupItem>,
+            WriteStorage<'a, WantsToUseItem>,
+        ),
+        (
+            ReadStorage<'a, Ally>,
+            ReadExpect<'a, Point>,
+            WriteExpect<'a, Noises>,
+        ),
+        (
+            WriteStorage<'a, Unaware>,
+            ReadExpect<'a, Entity>,
+            ReadStorage<'a, Equipped>,
+            ReadStorage<'a, DefenseBonus>,
+            ReadStorage<'a, Named>,
+            WriteExpect<'a, GameLog>,
+            WriteStorage<'a, Morale>,
+            ReadStorage<'a, Hasted>,
+            WriteStorage<'a, Surrendered>,
+            ReadStorage<'a, RangedWeapon>,
+        ),
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             mut map,
-            player_pos,
-            player_entity,
             runstate,
             entities,
             mut viewshed,
             monster,
             mut position,
             mut wants_to_melee,
+            group_members,
+            group_leaders,
+            confused,
+            mut asleep,
+            paralyzed,
+            mut rng,
+            mut memories,
+            combat_stats,
+            factions,
+            mut abilities,
+            mut wants_shoot,
+            mut wants_cast,
+            mut wants_summon,
+            ammunition,
+            backpack,
+            (items, healing, ranged_items, attributes, mut wants_pickup, mut wants_use),
+            (allies, player_pos, mut noises),
+            (
+                mut unaware,
+                player_entity,
+                equipped,
+                defense_bonuses,
+                names,
+                mut log,
+                mut morales,
+                hasted,
+                mut surrendered,
+                ranged_weapons,
+            ),
         ) = data;
-        if *runstate != RunState::MonsterTurn {
+        if *runstate != RunState::MonsterTurn && *runstate != RunState::HastedMonsterTurn {
             return;
         }
+
+        let player_stealth = stealth_dc(*player_entity, &attributes, &defense_bonuses, &equipped);
+
+        let mut leader_positions: HashMap<i32, Point> = HashMap::new();
+        for (member, _leader, pos) in (&group_members, &group_leaders, &position).join() {
+            leader_positions.insert(member.group_id, Point::new(pos.x, pos.y));
+        }
+        let kin_positions: Vec<(Entity, FactionId, Point)> =
+            (&entities, &monster, &factions, &combat_stats, &position)
+                .join()
+                .filter(|(_entity, _monster, _faction, stats, _pos)| stats.hp > 0)
+                .map(|(entity, _monster, faction, _stats, pos)| {
+                    (entity, faction.id, Point::new(pos.x, pos.y))
+                })
+                .collect();
+
         for (entity, viewshed, _monster, pos) in
             (&entities, &mut viewshed, &monster, &mut position).join()
         {
-            let distance =
-                rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
-            if distance < 1.5 {
-                wants_to_melee
-                    .insert(
-                        entity,
-                        WantsToMelee {
-                            target: *player_entity,
-                        },
-                    )
-                    .expect("unable to insert attack");
-            } else if viewshed.visible_tiles.contains(&*player_pos) {
-                let path = rltk::a_star_search(
-                    map.xy_idx(pos.x, pos.y) as i32,
-                    map.xy_idx(player_pos.x, player_pos.y) as i32,
-                    &mut *map,
-                );
-                if path.success && path.steps.len() > 1 {
-                    let mut idx = map.xy_idx(pos.x, pos.y);
-                    map.blocked[idx] = false;
-                    pos.x = path.steps[1] as i32 % map.width;
-                    pos.y = path.steps[1] as i32 / map.width;
-                    idx = map.xy_idx(pos.x, pos.y);
-                    map.blocked[idx] = true;
-                    viewshed.dirty = true;
+            //the extra turn is only for the hasted
+            if *runstate == RunState::HastedMonsterTurn && !hasted.contains(entity) {
+                continue;
+            }
+            if let Some(abilities) = abilities.get_mut(entity) {
+                for ability in abilities.abilities.iter_mut() {
+                    ability.cooldown_remaining = i32::max(0, ability.cooldown_remaining - 1);
+                }
+            }
+            //anything that hasn't noticed the player gets a chance to, even in its sleep
+            let mut player_unnoticed = false;
+            if unaware.contains(entity) && viewshed.visible_tiles.contains(&*player_pos) {
+                let distance =
+                    rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), *player_pos);
+                let wits = attributes
+                    .get(entity)
+                    .map_or(0, |a| a.intelligence.modifier());
+                let dozing = asleep.contains(entity);
+                if perception_roll(&mut rng, wits, distance, dozing) >= player_stealth {
+                    unaware.remove(entity);
+                    asleep.remove(entity);
+                    let seen = map.in_player_view(pos.x, pos.y);
+                    if let (Some(name), true) = (names.get(entity), seen) {
+                        if dozing {
+                            log.entries
+                                .push(format!("{} wakes up and notices you!", &name.name));
+                        } else {
+                            log.entries.push(format!("{} notices you!", &name.name));
+                        }
+                    }
+                } else {
+                    player_unnoticed = true;
+                }
+            }
+
+            if asleep.contains(entity) || paralyzed.contains(entity) {
+                continue;
+            }
+            if confused.contains(entity) {
+                stumble(&mut map, pos, viewshed, &mut rng);
+                continue;
+            }
+
+            let my_pos = Point::new(pos.x, pos.y);
+
+            //the nearest creature in view worth attacking, and everything worth running from
+            let mut nearest_hostile: Option<(Entity, Point, f32)> = None;
+            let mut hostiles: Vec<Point> = Vec::new();
+            let mut threats: Vec<Point> = Vec::new();
+            if let Some(my_faction) = factions.get(entity) {
+                for tile in viewshed.visible_tiles.iter() {
+                    let idx = map.xy_idx(tile.x, tile.y);
+                    for other in map.tile_content[idx].iter() {
+                        let their_faction = match factions.get(*other) {
+                            Some(faction) if *other != entity => faction,
+                            _ => continue,
+                        };
+                        if *other == *player_entity && player_unnoticed {
+                            continue;
+                        }
+                        if combat_stats.get(*other).is_none_or(|stats| stats.hp < 1)
+                            || surrendered.contains(*other)
+                        {
+                            continue;
+                        }
+                        let distance = rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *tile);
+                        match faction::reaction(my_faction.id, their_faction.id) {
+                            Reaction::Attack => {
+                                hostiles.push(*tile);
+                                if nearest_hostile.is_none_or(|(_, _, nearest)| distance < nearest)
+                                {
+                                    nearest_hostile = Some((*other, *tile, distance));
+                                }
+                            }
+                            Reaction::Flee => threats.push(*tile),
+                            Reaction::Ignore => {}
+                        }
+                    }
+                }
+            }
+
+            //anything bright enough to read can carry and use what it finds
+            let uses_items = attributes
+                .get(entity)
+                .is_some_and(|a| a.intelligence.base >= 8);
+            let carried: Vec<Entity> = if uses_items {
+                (&entities, &backpack)
+                    .join()
+                    .filter(|(_item, pack)| pack.owner == entity)
+                    .map(|(item, _pack)| item)
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            let wounded = combat_stats
+                .get(entity)
+                .is_some_and(|stats| stats.hp < stats.max_hp / 2);
+            if wounded {
+                if let Some(potion) = carried.iter().find(|item| healing.get(**item).is_some()) {
+                    wants_use
+                        .insert(
+                            entity,
+                            WantsToUseItem {
+                                item: *potion,
+                                target: None,
+                            },
+                        )
+                        .expect("unable to insert item use");
+                    continue;
+                }
+            }
+
+            let mut leader_pos: Option<Point> = None;
+            if let Some(member) = group_members.get(entity) {
+                if group_leaders.get(entity).is_none() {
+                    leader_pos = leader_positions.get(&member.group_id).copied();
+                }
+            }
+
+            //wounds weigh on morale, and a quiet moment steadies it again
+            let wound_penalty = match combat_stats.get(entity) {
+                Some(stats) if stats.hp < stats.max_hp / 4 => 6,
+                Some(stats) if stats.hp < stats.max_hp / 2 => 3,
+                _ => 0,
+            };
+            let nothing_in_sight = hostiles.is_empty() && threats.is_empty();
+            let broken = morales.get_mut(entity).is_some_and(|morale| {
+                if nothing_in_sight {
+                    morale.value = i32::min(morale.max, morale.value + 1);
+                }
+                morale.value - wound_penalty < MORALE_BREAK
+            });
+            //having given up, it waits out the fight and regroups once its nerve is back
+            let in_player_view = map.in_player_view(pos.x, pos.y);
+            if surrendered.contains(entity) {
+                let steady = !broken
+                    && morales
+                        .get(entity)
+                        .is_some_and(|morale| morale.value >= morale.max);
+                if !steady {
+                    continue;
+                }
+                surrendered.remove(entity);
+                if let (true, Some(name)) = (in_player_view, names.get(entity)) {
+                    log.entries
+                        .push(format!("{} takes up its arms again!", &name.name));
+                }
+            }
+            let mut target = nearest_hostile;
+            if broken {
+                threats.extend(hostiles.iter());
+                target = None;
+            }
+
+            let mut memory = memories.get_mut(entity);
+            if !threats.is_empty() {
+                if let Some(memory) = memory.as_mut() {
+                    memory.state = AiState::Fleeing;
+                    memory.last_seen = None;
+                }
+                if flee_from(&mut map, pos, viewshed, &threats) {
+                    continue;
+                }
+                //cornered: beg for mercy, or turn and fight
+                if broken && rng.roll_dice(1, 2) == 1 {
+                    surrendered
+                        .insert(entity, Surrendered {})
+                        .expect("unable to insert surrender");
+                    if let (true, Some(name)) = (in_player_view, names.get(entity)) {
+                        log.entries.push(format!(
+                            "{} throws down its arms and surrenders!",
+                            &name.name
+                        ));
+                    }
+                    continue;
+                }
+                target = nearest_hostile;
+                if target.is_none() {
+                    continue;
+                }
+            }
+            if let Some((target_entity, target_pos, distance)) = target {
+                if let Some(memory) = memory.as_mut() {
+                    memory.state = AiState::Chasing;
+                    memory.last_seen = Some(target_pos);
+                }
+
+                //abilities get first refusal, then it's down to teeth and claws
+                let clear_shot = map.line_of_fire(my_pos, target_pos).last() == Some(&target_pos);
+                let wielded_ammo = (&ranged_weapons, &equipped)
+                    .join()
+                    .find(|(_weapon, equipped_by)| equipped_by.owner == entity)
+                    .map(|(weapon, _equipped_by)| weapon.ammo);
+                let has_ammo = (&ammunition, &backpack).join().any(|(ammo, pack)| {
+                    pack.owner == entity && Some(ammo.ammo_type) == wielded_ammo
+                });
+                let scroll = carried.iter().find(|item| {
+                    ranged_items
+                        .get(**item)
+                        .is_some_and(|ranged| distance <= ranged.range as f32)
+                });
+                if let Some(scroll) = scroll {
+                    if clear_shot && rng.roll_dice(1, 2) == 1 {
+                        wants_use
+                            .insert(
+                                entity,
+                                WantsToUseItem {
+                                    item: *scroll,
+                                    target: Some(target_pos),
+                                },
+                            )
+                            .expect("unable to insert item use");
+                        continue;
+                    }
+                }
+                let chosen = abilities.get_mut(entity).and_then(|abilities| {
+                    choose_ability(abilities, distance, clear_shot, has_ammo, &mut rng)
+                });
+                if let Some(kind) = chosen {
+                    match kind {
+                        AbilityKind::Shoot => {
+                            wants_shoot
+                                .insert(entity, WantsToShoot { target: target_pos })
+                                .expect("unable to insert shot");
+                        }
+                        AbilityKind::Cast(spell) => {
+                            wants_cast
+                                .insert(
+                                    entity,
+                                    WantsToCastSpell {
+                                        spell,
+                                        target: target_pos,
+                                    },
+                                )
+                                .expect("unable to insert spell");
+                        }
+                        AbilityKind::Summon(monster) => {
+                            wants_summon
+                                .insert(entity, WantsToSummon { monster })
+                                .expect("unable to insert summon");
+                        }
+                    }
+                    continue;
+                }
+
+                if distance < 1.5 {
+                    wants_to_melee
+                        .insert(
+                            entity,
+                            WantsToMelee {
+                                target: target_entity,
+                            },
+                        )
+                        .expect("unable to insert attack");
+                } else {
+                    path_towards(&mut map, pos, viewshed, target_pos);
+                }
+                continue;
+            }
+            //companions keep to the player's heels when there's no fight
+            if allies.contains(entity) {
+                if rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *player_pos) > ALLY_LEASH {
+                    path_towards(&mut map, pos, viewshed, *player_pos);
+                }
+                continue;
+            }
+
+            //nothing to fight, so see what's lying about
+            if uses_items {
+                let idx = map.xy_idx(pos.x, pos.y);
+                if let Some(item) = map.tile_content[idx]
+                    .iter()
+                    .find(|e| items.get(**e).is_some())
+                {
+                    wants_pickup
+                        .insert(
+                            entity,
+                            WantsToPickupItem {
+                                collected_by: entity,
+                                item: *item,
+                            },
+                        )
+                        .expect("unable to insert pickup");
+                    continue;
+                }
+            }
+
+            //out of sight: a group keeps together before anything else
+            if let Some(leader_pos) = leader_pos {
+                if rltk::DistanceAlg::Pythagoras.distance2d(my_pos, leader_pos) > LEADER_LEASH {
+                    path_towards(&mut map, pos, viewshed, leader_pos);
+                    continue;
                 }
             }
+
+            let memory = match memory {
+                None => continue,
+                Some(memory) => memory,
+            };
+
+            //only those out for the player's blood track its noise and scent
+            let hunts_player = factions
+                .get(entity)
+                .is_some_and(|f| faction::reaction(f.id, FactionId::Player) == Reaction::Attack);
+
+            //the nearest sound in earshot is worth a look, unless running away
+            if hunts_player && memory.state != AiState::Fleeing {
+                let heard = noises
+                    .sounds
+                    .iter()
+                    .map(|(at, loudness)| {
+                        (
+                            *at,
+                            *loudness,
+                            rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *at),
+                        )
+                    })
+                    .filter(|(_at, loudness, distance)| distance <= loudness)
+                    .min_by(|a, b| a.2.total_cmp(&b.2));
+                if let Some((at, _loudness, _distance)) = heard {
+                    memory.state = AiState::Searching;
+                    memory.last_seen = Some(at);
+                    memory.search_turns = SEARCH_TURNS;
+                }
+            }
+
+            match memory.state {
+                AiState::Chasing => {
+                    memory.state = AiState::Searching;
+                    memory.search_turns = SEARCH_TURNS;
+                    if let Some(last_seen) = memory.last_seen {
+                        path_towards(&mut map, pos, viewshed, last_seen);
+                    }
+                }
+                AiState::Searching => {
+                    memory.search_turns -= 1;
+                    match memory.last_seen {
+                        Some(last_seen) if memory.search_turns > 0 && my_pos != last_seen => {
+                            path_towards(&mut map, pos, viewshed, last_seen);
+                        }
+                        _ => {
+                            //nothing at the last known spot, so fall back on the nose
+                            memory.last_seen = None;
+                            if hunts_player
+                                && memory.search_turns > 0
+                                && follow_scent(&mut map, pos, viewshed)
+                            {
+                                memory.search_turns = SEARCH_TURNS;
+                            } else {
+                                memory.state = AiState::Wandering;
+                            }
+                        }
+                    }
+                }
+                AiState::Fleeing => {
+                    //safely out of sight, lie low until the nerve comes back, then find the others
+                    memory.last_seen = None;
+                    if !broken {
+                        let kin = factions.get(entity).and_then(|mine| {
+                            kin_positions
+                                .iter()
+                                .filter(|(other, faction, _at)| {
+                                    *other != entity && *faction == mine.id
+                                })
+                                .map(|(_other, _faction, at)| {
+                                    (*at, rltk::DistanceAlg::Pythagoras.distance2d(my_pos, *at))
+                                })
+                                .min_by(|a, b| a.1.total_cmp(&b.1))
+                        });
+                        match kin {
+                            Some((kin_pos, distance)) if distance > KIN_LEASH => {
+                                path_towards(&mut map, pos, viewshed, kin_pos);
+                            }
+                            _ => memory.state = AiState::Idle,
+                        }
+                    }
+                }
+                AiState::Idle | AiState::Wandering
+                    if hunts_player && !broken && map.scent[map.xy_idx(pos.x, pos.y)] > 0 =>
+                {
+                    //picked up a fresh trail
+                    memory.state = AiState::Searching;
+                    memory.search_turns = SEARCH_TURNS;
+                    follow_scent(&mut map, pos, viewshed);
+                }
+                AiState::Idle => {
+                    if !broken && rng.roll_dice(1, 10) == 1 {
+                        memory.state = AiState::Wandering;
+                    }
+                }
+                AiState::Wandering => {
+                    if rng.roll_dice(1, 20) == 1 {
+                        memory.state = AiState::Idle;
+                    } else if leader_pos.is_none() && rng.roll_dice(1, 3) == 1 {
+                        stumble(&mut map, pos, viewshed, &mut rng);
+                    }
+                }
+            }
+        }
+        noises.sounds.clear();
+    }
+}
+
+fn choose_ability(
+    abilities: &mut Abilities,
+    distance: f32,
+    clear_shot: bool,
+    has_ammo: bool,
+    rng: &mut RandomNumberGenerator,
+) -> Option<AbilityKind> {
+    for ability in abilities.abilities.iter_mut() {
+        if ability.cooldown_remaining > 0 || distance > ability.range as f32 {
+            continue;
+        }
+        let usable = match ability.kind {
+            AbilityKind::Shoot => clear_shot && has_ammo,
+            AbilityKind::Cast(_) => clear_shot,
+            AbilityKind::Summon(_) => true,
+        };
+        if usable && rng.roll_dice(1, 100) <= ability.chance {
+            ability.cooldown_remaining = ability.cooldown;
+            return Some(ability.kind.clone());
+        }
+    }
+    None
+}
+
+//step onto the neighbouring tile where the player's scent is strongest, if it's stronger than here
+fn follow_scent(map: &mut Map, pos: &mut Position, viewshed: &mut Viewshed) -> bool {
+    let idx = map.xy_idx(pos.x, pos.y);
+    let mut best_idx = idx;
+    let mut best_scent = map.scent[idx];
+    for (exit, _cost) in map.get_available_exits(idx).iter() {
+        if map.scent[*exit] > best_scent {
+            best_idx = *exit;
+            best_scent = map.scent[*exit];
+        }
+    }
+    if best_idx == idx {
+        return false;
+    }
+    move_to(map, pos, viewshed, best_idx);
+    true
+}
+
+fn move_to(map: &mut Map, pos: &mut Position, viewshed: &mut Viewshed, idx: usize) {
+    let old_idx = map.xy_idx(pos.x, pos.y);
+    map.blocked[old_idx] = false;
+    pos.x = idx as i32 % map.width;
+    pos.y = idx as i32 / map.width;
+    map.blocked[idx] = true;
+    viewshed.dirty = true;
+}
+
+//whoever we're after usually blocks their own tile, so the search is allowed to end on it
+fn path_towards(map: &mut Map, pos: &mut Position, viewshed: &mut Viewshed, target: Point) {
+    let goal = map.xy_idx(target.x, target.y);
+    let goal_blocked = map.blocked[goal];
+    map.blocked[goal] = false;
+    let path = rltk::a_star_search(map.xy_idx(pos.x, pos.y) as i32, goal as i32, &*map);
+    map.blocked[goal] = goal_blocked;
+    if path.success && path.steps.len() > 1 && !map.blocked[path.steps[1]] {
+        move_to(map, pos, viewshed, path.steps[1]);
+    }
+}
+
+//run down the flee map, away from every threat at once; false when there's nowhere further to go
+fn flee_from(
+    map: &mut Map,
+    pos: &mut Position,
+    viewshed: &mut Viewshed,
+    threats: &[Point],
+) -> bool {
+    let idx = map.xy_idx(pos.x, pos.y);
+    let starts: Vec<usize> = threats.iter().map(|t| map.xy_idx(t.x, t.y)).collect();
+    //our own tile is blocked by us, which would leave it off the map
+    map.blocked[idx] = false;
+    let flee_map = DijkstraMap::new(map.width, map.height, &starts, &*map, FLEE_DEPTH);
+    map.blocked[idx] = true;
+    match DijkstraMap::find_highest_exit(&flee_map, idx, &*map) {
+        Some(exit) if flee_map.map[exit] > flee_map.map[idx] => {
+            move_to(map, pos, viewshed, exit);
+            true
+        }
+        _ => false,
+    }
+}
+
+fn stumble(
+    map: &mut Map,
+    pos: &mut Position,
+    viewshed: &mut Viewshed,
+    rng: &mut RandomNumberGenerator,
+) {
+    let idx = map.xy_idx(pos.x, pos.y);
+    let exits = map.get_available_exits(idx);
+    if !exits.is_empty() {
+        let choice = rng.roll_dice(1, exits.len() as i32) as usize - 1;
+        move_to(map, pos, viewshed, exits[choice].0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::open_map;
+
+    fn viewshed() -> Viewshed {
+        Viewshed {
+            visible_tiles: Vec::new(),
+            range: 8,
+            dirty: false,
         }
     }
+
+    #[test]
+    fn follower_closes_on_its_blocking_leader() {
+        let mut map = open_map();
+        let leader = Point::new(30, 20);
+        let leader_idx = map.xy_idx(leader.x, leader.y);
+        map.blocked[leader_idx] = true;
+        let mut pos = Position { x: 10, y: 20 };
+        let start_idx = map.xy_idx(pos.x, pos.y);
+        map.blocked[start_idx] = true;
+        let mut viewshed = viewshed();
+
+        path_towards(&mut map, &mut pos, &mut viewshed, leader);
+
+        let before = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(10, 20), leader);
+        let after = rltk::DistanceAlg::Pythagoras.distance2d(Point::new(pos.x, pos.y), leader);
+        assert!(after < before);
+        assert!(map.blocked[leader_idx]);
+    }
+
+    #[test]
+    fn follower_never_steps_onto_its_leader() {
+        let mut map = open_map();
+        let leader = Point::new(11, 20);
+        let leader_idx = map.xy_idx(leader.x, leader.y);
+        map.blocked[leader_idx] = true;
+        let mut pos = Position { x: 10, y: 20 };
+        let mut viewshed = viewshed();
+
+        path_towards(&mut map, &mut pos, &mut viewshed, leader);
+
+        assert_eq!((pos.x, pos.y), (10, 20));
+    }
 }
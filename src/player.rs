@@ -1,17 +1,41 @@
+use crate::faction::{self, Reaction};
 use crate::gamelog::GameLog;
-use crate::{components::*, map::*, RunState, State};
-use rltk::{Point, Rltk, VirtualKeyCode};
+use crate::scent_system::{Noises, SCENT_STRENGTH};
+use crate::{components::*, map::*, regen_system::hostile_in_view, RunState, State};
+use rltk::{Point, RandomNumberGenerator, Rltk, VirtualKeyCode};
 use specs::prelude::*;
-use std::cmp::{max, min};
 
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
+    let (mut delta_x, mut delta_y) = (delta_x, delta_y);
+    if ecs
+        .read_storage::<Confused>()
+        .contains(*ecs.fetch::<Entity>())
+    {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        //always a step somewhere, never back onto the player's own tile
+        loop {
+            delta_x = rng.roll_dice(1, 3) - 2;
+            delta_y = rng.roll_dice(1, 3) - 2;
+            if (delta_x, delta_y) != (0, 0) {
+                break;
+            }
+        }
+        ecs.fetch_mut::<GameLog>()
+            .entries
+            .push("You stumble around in confusion.".to_string());
+    }
+
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
     let combat_stats = ecs.read_storage::<CombatStats>();
     let entities = ecs.entities();
     let mut wants_to_melee = ecs.write_storage::<WantsToMelee>();
-    let map = ecs.fetch::<Map>();
+    let mut map = ecs.fetch_mut::<Map>();
+    let allies = ecs.read_storage::<Ally>();
+    let surrendered = ecs.read_storage::<Surrendered>();
+    let factions = ecs.read_storage::<Faction>();
+    let mut swap: Option<(Entity, i32, i32)> = None;
 
     for (entity, _player, pos, viewshed) in
         (&entities, &mut players, &mut positions, &mut viewsheds).join()
@@ -25,6 +49,28 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
         let destination_idx = map.xy_idx(pos.x + delta_x, pos.y + delta_y);
 
+        //friends, neutrals and foes that have given up trade places instead of fighting
+        let my_faction = factions.get(entity).map(|f| f.id);
+        if let Some(ally) = map.tile_content[destination_idx].iter().find(|e| {
+            allies.contains(**e)
+                || surrendered.contains(**e)
+                || my_faction
+                    .zip(factions.get(**e))
+                    .is_some_and(|(mine, theirs)| {
+                        faction::reaction(mine, theirs.id) != Reaction::Attack
+                    })
+        }) {
+            swap = Some((*ally, pos.x, pos.y));
+            map.scent[destination_idx] = SCENT_STRENGTH;
+            pos.x += delta_x;
+            pos.y += delta_y;
+            viewshed.dirty = true;
+            let mut player_pos = ecs.write_resource::<Point>();
+            player_pos.x = pos.x;
+            player_pos.y = pos.y;
+            break;
+        }
+
         for potential_target in map.tile_content[destination_idx].iter() {
             let target = combat_stats.get(*potential_target);
             if let Some(_target) = target {
@@ -41,18 +87,53 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) {
         }
 
         if !map.blocked[destination_idx] {
-            pos.x = min(79, max(0, pos.x + delta_x));
-            pos.y = min(49, max(0, pos.y + delta_y));
+            pos.x = (pos.x + delta_x).clamp(0, 79);
+            pos.y = (pos.y + delta_y).clamp(0, 49);
 
             viewshed.dirty = true;
             let mut player_pos = ecs.write_resource::<Point>();
             player_pos.x = pos.x;
             player_pos.y = pos.y;
+            //every step leaves a trail, even the extra ones a hasted player gets
+            map.scent[destination_idx] = SCENT_STRENGTH;
+            //footsteps carry a little way
+            ecs.write_resource::<Noises>()
+                .make(Point::new(pos.x, pos.y), 3.0);
+        }
+    }
+
+    if let Some((ally, x, y)) = swap {
+        if let Some(ally_pos) = positions.get_mut(ally) {
+            ally_pos.x = x;
+            ally_pos.y = y;
+        }
+        if let Some(ally_viewshed) = viewsheds.get_mut(ally) {
+            ally_viewshed.dirty = true;
         }
     }
 }
 
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
+    {
+        let player_entity = *gs.ecs.fetch::<Entity>();
+        if gs.ecs.read_storage::<Asleep>().contains(player_entity) {
+            return RunState::PlayerTurn;
+        }
+        if gs.ecs.read_storage::<Paralyzed>().contains(player_entity) {
+            gs.ecs
+                .fetch_mut::<GameLog>()
+                .entries
+                .push("You are paralyzed and cannot move!".to_string());
+            return RunState::PlayerTurn;
+        }
+        if gs.ecs.read_storage::<Resting>().contains(player_entity) {
+            if keep_resting(&gs.ecs) && ctx.key.is_none() {
+                return RunState::PlayerTurn;
+            }
+            gs.ecs.write_storage::<Resting>().remove(player_entity);
+        }
+    }
+
     match ctx.key {
         None => return RunState::AwaitingInput,
         Some(key) => match key {
@@ -69,13 +150,19 @@ pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
 
             //other
             VirtualKeyCode::Escape => rltk::BTerm::quit(ctx),
-            VirtualKeyCode::G => get_item(&mut gs.ecs),
+            VirtualKeyCode::G => get_item(&gs.ecs),
             VirtualKeyCode::B | VirtualKeyCode::I => return RunState::ShowInventory,
             VirtualKeyCode::D => return RunState::ShowDropItem,
+            VirtualKeyCode::R => return RunState::ShowRemoveItem,
+            VirtualKeyCode::F => return fire_weapon(&gs.ecs),
+
+            //waiting
+            VirtualKeyCode::Numpad5 | VirtualKeyCode::Space => {}
+            VirtualKeyCode::Z => return start_resting(&gs.ecs),
             _ => return RunState::AwaitingInput,
         },
     }
-    return RunState::PlayerTurn;
+    RunState::PlayerTurn
 }
 
 fn get_item(ecs: &World) {
@@ -109,3 +196,128 @@ fn get_item(ecs: &World) {
         }
     }
 }
+
+fn fire_weapon(ecs: &World) -> RunState {
+    let player_entity = ecs.fetch::<Entity>();
+    let entities = ecs.entities();
+    let ranged_weapons = ecs.read_storage::<RangedWeapon>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let ammunition = ecs.read_storage::<Ammunition>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    let weapon = (&entities, &ranged_weapons, &equipped)
+        .join()
+        .find(|(_item, _weapon, equipped_by)| equipped_by.owner == *player_entity);
+    match weapon {
+        None => {
+            gamelog
+                .entries
+                .push("You have no ranged weapon equipped.".to_string());
+            RunState::AwaitingInput
+        }
+        Some((weapon_entity, weapon, _equipped_by)) => {
+            let has_ammo = (&ammunition, &backpack)
+                .join()
+                .any(|(ammo, pack)| pack.owner == *player_entity && ammo.ammo_type == weapon.ammo);
+            if has_ammo {
+                RunState::ShowFireTargeting {
+                    range: weapon.range,
+                    weapon: weapon_entity,
+                }
+            } else {
+                gamelog
+                    .entries
+                    .push(format!("You are out of {}.", weapon.ammo.name()));
+                RunState::AwaitingInput
+            }
+        }
+    }
+}
+
+fn start_resting(ecs: &World) -> RunState {
+    let player_entity = *ecs.fetch::<Entity>();
+    if !keep_resting(ecs) {
+        return RunState::AwaitingInput;
+    }
+    ecs.write_storage::<Resting>()
+        .insert(player_entity, Resting {})
+        .expect("unable to insert resting");
+    ecs.fetch_mut::<GameLog>()
+        .entries
+        .push("You settle down to rest.".to_string());
+    RunState::PlayerTurn
+}
+
+//resting stops once healed, or as soon as something worth waking up for happens
+fn keep_resting(ecs: &World) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let factions = ecs.read_storage::<Faction>();
+    let surrendered = ecs.read_storage::<Surrendered>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let stats = ecs.read_storage::<CombatStats>();
+    let hunger = ecs.read_storage::<HungerClock>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    if let Some(viewshed) = viewsheds.get(player_entity) {
+        if hostile_in_view(viewshed, &map, &factions, &surrendered) {
+            gamelog
+                .entries
+                .push("You cannot rest with enemies nearby.".to_string());
+            return false;
+        }
+    }
+    if hunger
+        .get(player_entity)
+        .is_some_and(|clock| clock.state.stops_healing())
+    {
+        gamelog
+            .entries
+            .push("You are too hungry to rest.".to_string());
+        return false;
+    }
+    if stats
+        .get(player_entity)
+        .is_some_and(|stat| stat.hp >= stat.max_hp)
+    {
+        gamelog.entries.push("You are fully rested.".to_string());
+        return false;
+    }
+    true
+}
+
+pub fn player_has_level_up(ecs: &World) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    ecs.read_storage::<Experience>()
+        .get(player_entity)
+        .is_some_and(|exp| exp.pending_level_ups > 0)
+}
+
+pub fn apply_level_up(ecs: &mut World, choice: LevelUpChoice) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut experience = ecs.write_storage::<Experience>();
+    let mut attributes = ecs.write_storage::<Attributes>();
+    let mut stats = ecs.write_storage::<CombatStats>();
+    if let Some(exp) = experience.get_mut(player_entity) {
+        exp.pending_level_ups -= 1;
+    }
+    if let Some(attr) = attributes.get_mut(player_entity) {
+        match choice {
+            LevelUpChoice::Might => attr.might.base += 1,
+            LevelUpChoice::Fitness => attr.fitness.base += 1,
+            LevelUpChoice::Quickness => attr.quickness.base += 1,
+            LevelUpChoice::Intelligence => attr.intelligence.base += 1,
+            LevelUpChoice::Toughness => {}
+        }
+    }
+    if choice == LevelUpChoice::Toughness {
+        if let Some(stat) = stats.get_mut(player_entity) {
+            stat.max_hp += 10;
+            stat.hp += 10;
+        }
+    }
+    ecs.fetch_mut::<GameLog>()
+        .entries
+        .push(format!("You gain {}.", choice.description()));
+}
@@ -0,0 +1,262 @@
+use crate::combat::{armor_class, encumbrance_penalty, roll_damage, roll_to_hit, AttackResult};
+use crate::particle_system::ParticleBuilder;
+use crate::scent_system::Noises;
+use crate::{
+    Ammunition, Attributes, CombatStats, DefenseBonus, Equipped, GameLog, InBackpack, Item, Map,
+    Named, NaturalAttackDefense, Position, RangedWeapon, Stackable, SufferDamage, WantsToShoot,
+};
+use rltk::{Point, RandomNumberGenerator, RGB};
+use specs::prelude::*;
+
+pub struct RangedCombatSystem {}
+
+impl<'a> System<'a> for RangedCombatSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToShoot>,
+        ReadStorage<'a, Named>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, RangedWeapon>,
+        ReadStorage<'a, Ammunition>,
+        ReadStorage<'a, InBackpack>,
+        ReadStorage<'a, Equipped>,
+        ReadStorage<'a, Attributes>,
+        ReadStorage<'a, NaturalAttackDefense>,
+        ReadStorage<'a, DefenseBonus>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, RandomNumberGenerator>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, Noises>,
+        ReadStorage<'a, Item>,
+        WriteStorage<'a, Stackable>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut wants_shoot,
+            names,
+            combat_stats,
+            positions,
+            map,
+            ranged_weapons,
+            ammunition,
+            backpack,
+            equipped,
+            attributes,
+            natural_attacks,
+            defense_bonuses,
+            mut suffer_damage,
+            mut log,
+            mut rng,
+            mut particle_builder,
+            mut noises,
+            items,
+            mut stacks,
+        ) = data;
+
+        for (entity, shot, name, pos) in (&entities, &wants_shoot, &names, &positions).join() {
+            let weapon = (&entities, &ranged_weapons, &equipped)
+                .join()
+                .find(|(_item, _weapon, equipped_by)| equipped_by.owner == entity)
+                .map(|(item, weapon, _equipped_by)| (item, weapon));
+            let (weapon_entity, weapon) = match weapon {
+                None => continue,
+                Some(weapon) => weapon,
+            };
+            let weapon_name = names
+                .get(weapon_entity)
+                .map_or("a weapon".to_string(), |n| n.name.clone());
+            let shooter_seen = map.in_player_view(pos.x, pos.y);
+
+            let ammo = (&entities, &ammunition, &backpack)
+                .join()
+                .find(|(_item, ammo, pack)| pack.owner == entity && ammo.ammo_type == weapon.ammo)
+                .map(|(item, _ammo, _pack)| item);
+            match ammo {
+                None => {
+                    if shooter_seen {
+                        log.entries.push(format!(
+                            "{} has no {} for {}.",
+                            &name.name,
+                            weapon.ammo.name(),
+                            weapon_name
+                        ));
+                    }
+                    continue;
+                }
+                Some(ammo) => match stacks.get_mut(ammo) {
+                    Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+                    _ => entities.delete(ammo).expect("unable to delete ammunition"),
+                },
+            }
+
+            let path = map.line_of_fire(Point::new(pos.x, pos.y), shot.target);
+            noises.make(Point::new(pos.x, pos.y), 6.0);
+            for tile in path.iter() {
+                particle_builder.request(
+                    tile.x,
+                    tile.y,
+                    RGB::named(rltk::YELLOW),
+                    RGB::named(rltk::BLACK),
+                    rltk::to_cp437('∙'),
+                    100.0,
+                );
+            }
+            let victim = path.last().and_then(|impact| {
+                let idx = map.xy_idx(impact.x, impact.y);
+                map.tile_content[idx]
+                    .iter()
+                    .find(|e| **e != entity && combat_stats.get(**e).is_some())
+                    .copied()
+            });
+            let seen = map.player_sees_either_end(Point::new(pos.x, pos.y), &path);
+            let victim = match victim {
+                None => {
+                    if seen {
+                        log.entries.push(format!(
+                            "{} fires {}, but hits nothing.",
+                            &name.name, weapon_name
+                        ));
+                    }
+                    continue;
+                }
+                Some(victim) => victim,
+            };
+            let victim_name = names.get(victim).unwrap();
+
+            let quickness = attributes.get(entity).map_or(0, |a| a.quickness.modifier())
+                - encumbrance_penalty(entity, &attributes, &items, &backpack, &equipped, &stacks);
+            let armor_class = armor_class(
+                victim,
+                &natural_attacks,
+                &attributes,
+                &defense_bonuses,
+                &equipped,
+            );
+            let (result, attack_roll) =
+                roll_to_hit(&mut rng, quickness + weapon.hit_bonus, armor_class);
+            match result {
+                AttackResult::Fumble if seen => log.entries.push(format!(
+                    "{} fumbles a shot at {} with {}.",
+                    &name.name, &victim_name.name, weapon_name
+                )),
+                AttackResult::Miss if seen => log.entries.push(format!(
+                    "{} shoots at {} with {} and misses. ({} vs AC {})",
+                    &name.name, &victim_name.name, weapon_name, attack_roll, armor_class
+                )),
+                AttackResult::Hit | AttackResult::Critical => {
+                    let damage = roll_damage(
+                        &mut rng,
+                        result,
+                        weapon.damage_n_dice,
+                        weapon.damage_die_type,
+                        weapon.damage_bonus,
+                    );
+                    if seen && result == AttackResult::Critical {
+                        log.entries.push(format!(
+                            "Critical! {} shoots {} with {} for {} hp!",
+                            &name.name, &victim_name.name, weapon_name, damage
+                        ));
+                    } else if seen {
+                        log.entries.push(format!(
+                            "{} shoots {} with {} for {} hp! ({} vs AC {})",
+                            &name.name,
+                            &victim_name.name,
+                            weapon_name,
+                            damage,
+                            attack_roll,
+                            armor_class
+                        ));
+                    }
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        victim,
+                        damage,
+                        weapon.damage_type,
+                        Some(entity),
+                    );
+                    if let Some(impact) = path.last() {
+                        particle_builder.request(
+                            impact.x,
+                            impact.y,
+                            RGB::named(rltk::ORANGE),
+                            RGB::named(rltk::BLACK),
+                            rltk::to_cp437('‼'),
+                            200.0,
+                        );
+                    }
+                }
+                AttackResult::Fumble | AttackResult::Miss => {}
+            }
+        }
+        wants_shoot.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::open_map;
+    use crate::{AmmoType, DamageType, EquipmentSlot};
+
+    #[test]
+    fn firing_the_last_arrow_deletes_it() {
+        let mut system = RangedCombatSystem {};
+        let mut ecs = World::new();
+        System::setup(&mut system, &mut ecs);
+        ecs.insert(open_map());
+        ecs.insert(GameLog {
+            entries: Vec::new(),
+        });
+        ecs.insert(RandomNumberGenerator::seeded(1));
+        ecs.insert(ParticleBuilder::default());
+        ecs.insert(Noises::default());
+        let archer = ecs
+            .create_entity()
+            .with(Named {
+                name: "Archer".to_string(),
+            })
+            .with(Position { x: 10, y: 10 })
+            .build();
+        ecs.create_entity()
+            .with(RangedWeapon {
+                range: 8,
+                ammo: AmmoType::Arrow,
+                hit_bonus: 0,
+                damage_n_dice: 1,
+                damage_die_type: 6,
+                damage_bonus: 0,
+                damage_type: DamageType::Piercing,
+            })
+            .with(Equipped {
+                owner: archer,
+                slot: EquipmentSlot::Ranged,
+            })
+            .build();
+        let arrow = ecs
+            .create_entity()
+            .with(Ammunition {
+                ammo_type: AmmoType::Arrow,
+            })
+            .with(Stackable { quantity: 1 })
+            .with(InBackpack { owner: archer })
+            .build();
+        ecs.write_storage::<WantsToShoot>()
+            .insert(
+                archer,
+                WantsToShoot {
+                    target: Point::new(15, 10),
+                },
+            )
+            .unwrap();
+
+        system.run_now(&ecs);
+        ecs.maintain();
+
+        assert!(!ecs.is_alive(arrow));
+    }
+}
@@ -0,0 +1,77 @@
+use crate::faction::{self, FactionId, Reaction};
+use crate::{CombatStats, Faction, HungerClock, Map, Regenerates, RunState, Surrendered, Viewshed};
+use specs::prelude::*;
+
+pub struct RegenerationSystem {}
+
+impl<'a> System<'a> for RegenerationSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, RunState>,
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, Regenerates>,
+        WriteStorage<'a, CombatStats>,
+        ReadStorage<'a, Viewshed>,
+        ReadStorage<'a, Faction>,
+        ReadStorage<'a, HungerClock>,
+        ReadStorage<'a, Surrendered>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            runstate,
+            map,
+            mut regenerates,
+            mut stats,
+            viewsheds,
+            factions,
+            hunger,
+            surrendered,
+        ) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        for (entity, regen, stat, viewshed) in
+            (&entities, &mut regenerates, &mut stats, &viewsheds).join()
+        {
+            //an empty stomach stops the healing, as does any monster in sight
+            let famished = hunger
+                .get(entity)
+                .is_some_and(|clock| clock.state.stops_healing());
+            if famished
+                || stat.hp >= stat.max_hp
+                || stat.hp < 1
+                || hostile_in_view(viewshed, &map, &factions, &surrendered)
+            {
+                regen.counter = 0;
+                continue;
+            }
+            regen.counter += 1;
+            if regen.counter >= regen.interval {
+                regen.counter = 0;
+                stat.hp += 1;
+            }
+        }
+    }
+}
+
+//anything in view that would attack the player on sight
+pub fn hostile_in_view(
+    viewshed: &Viewshed,
+    map: &Map,
+    factions: &ReadStorage<Faction>,
+    surrendered: &ReadStorage<Surrendered>,
+) -> bool {
+    viewshed.visible_tiles.iter().any(|tile| {
+        let idx = map.xy_idx(tile.x, tile.y);
+        map.tile_content[idx].iter().any(|e| {
+            !surrendered.contains(*e)
+                && factions.get(*e).is_some_and(|faction| {
+                    faction::reaction(faction.id, FactionId::Player) == Reaction::Attack
+                })
+        })
+    })
+}
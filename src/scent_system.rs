@@ -0,0 +1,45 @@
+use crate::{Map, Player, Position, RunState};
+use rltk::Point;
+use specs::prelude::*;
+
+pub const SCENT_STRENGTH: i32 = 50;
+
+//sounds made this round; monsters within earshot go and look
+#[derive(Default)]
+pub struct Noises {
+    pub sounds: Vec<(Point, f32)>,
+}
+
+impl Noises {
+    pub fn make(&mut self, at: Point, loudness: f32) {
+        self.sounds.push((at, loudness));
+    }
+}
+
+pub struct ScentSystem {}
+
+impl<'a> System<'a> for ScentSystem {
+    type SystemData = (
+        WriteExpect<'a, Map>,
+        ReadExpect<'a, RunState>,
+        ReadStorage<'a, Player>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (mut map, runstate, players, positions) = data;
+
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        //old trails fade a little each round, the player's tile is always fresh
+        for scent in map.scent.iter_mut() {
+            *scent = i32::max(0, *scent - 1);
+        }
+        for (_player, pos) in (&players, &positions).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            map.scent[idx] = SCENT_STRENGTH;
+        }
+    }
+}
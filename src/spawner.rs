@@ -1,11 +1,31 @@
 use crate::components::*;
-use crate::map::MAP_WIDTH;
+use crate::faction::FactionId;
+use crate::gamelog::{with_article, GameLog};
+use crate::identification::Identification;
+use crate::map::{Map, MAP_WIDTH};
+use crate::random_table::RandomTable;
 use crate::rect::*;
-use rltk::{RandomNumberGenerator, RGB};
+use rltk::{BaseMap, RandomNumberGenerator, RGB};
 use specs::prelude::*;
+use std::collections::HashSet;
 
 pub const MAX_MONSTERS: i32 = 3;
 pub const MAX_ITEMS: i32 = 5;
+pub const MAX_GROUP_SIZE: i32 = 4;
+
+pub struct GroupIds {
+    pub next: i32,
+}
+
+#[derive(Default)]
+pub struct UniqueRegistry {
+    pub spawned: HashSet<String>,
+    pub slain: Vec<String>,
+}
+
+//name, depth it appears on, how to build it
+type UniqueSpawn = fn(&mut World, i32, i32);
+const UNIQUES: [(&str, i32, UniqueSpawn); 1] = [("Grishnak the Orc Warlord", 1, grishnak)];
 
 pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
     ecs.create_entity()
@@ -28,40 +48,314 @@ pub fn player(ecs: &mut World, player_x: i32, player_y: i32) -> Entity {
         .with(Named {
             name: "Player".to_string(),
         })
-        .with(CombatStats {
-            max_hp: 69,
-            hp: 30,
-            defense: 2,
-            power: 5,
+        .with(CombatStats { max_hp: 69, hp: 30 })
+        .with(Attributes::new(13, 12, 12, 10))
+        .with(Experience {
+            level: 1,
+            xp: 0,
+            pending_level_ups: 0,
+        })
+        .with(HungerClock {
+            state: HungerState::WellFed,
+            duration: 20,
+        })
+        .with(Regenerates {
+            interval: 5,
+            counter: 0,
+        })
+        .with(Faction {
+            id: FactionId::Player,
         })
         .build()
 }
 
+fn monster_table() -> RandomTable {
+    RandomTable::new()
+        .add("Orc", 4)
+        .add("Goblin", 4)
+        .add("Fire Elemental", 1)
+        .add("Peasant", 1)
+        .add("Goblin Archer", 2)
+        .add("Orc Shaman", 1)
+}
+
 pub fn random_monster(ecs: &mut World, x: i32, y: i32) {
-    let roll: i32;
+    let roll: Option<String>;
     {
         let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        roll = rng.roll_dice(1, 2);
+        roll = monster_table().roll(&mut rng);
+    }
+    if let Some(name) = roll {
+        let monster = spawn_named_monster(ecs, &name, x, y);
+        //some are found dozing
+        let sleeps = ecs
+            .write_resource::<RandomNumberGenerator>()
+            .roll_dice(1, 4)
+            == 1;
+        if let (Some(monster), true) = (monster, sleeps) {
+            ecs.write_storage::<Asleep>()
+                .insert(monster, Asleep { turns: 200 })
+                .expect("unable to insert sleep");
+        }
     }
-    match roll {
-        1 => orc(ecs, x, y),
-        _ => goblin(ecs, x, y),
+}
+
+pub fn spawn_named_monster(ecs: &mut World, name: &str, x: i32, y: i32) -> Option<Entity> {
+    match name {
+        "Orc" => Some(orc(ecs, x, y)),
+        "Orc Shaman" => Some(orc_shaman(ecs, x, y)),
+        "Goblin" => Some(goblin(ecs, x, y)),
+        "Goblin Archer" => Some(goblin_archer(ecs, x, y)),
+        "Fire Elemental" => Some(fire_elemental(ecs, x, y)),
+        "Peasant" => Some(peasant(ecs, x, y)),
+        _ => None,
     }
 }
 
+//summoners call up help on a free tile next to them
+pub fn summon_monsters(ecs: &mut World) {
+    let mut to_spawn: Vec<(String, i32, i32)> = Vec::new();
+    {
+        let entities = ecs.entities();
+        let mut wants_summon = ecs.write_storage::<WantsToSummon>();
+        let positions = ecs.read_storage::<Position>();
+        let names = ecs.read_storage::<Named>();
+        let mut map = ecs.write_resource::<Map>();
+        let mut log = ecs.write_resource::<GameLog>();
+        for (entity, summon, pos) in (&entities, &wants_summon, &positions).join() {
+            let idx = map.xy_idx(pos.x, pos.y);
+            let free = map
+                .get_available_exits(idx)
+                .iter()
+                .map(|(exit, _cost)| *exit)
+                .find(|exit| !map.blocked[*exit]);
+            if let Some(exit) = free {
+                map.blocked[exit] = true;
+                let (x, y) = (exit as i32 % map.width, exit as i32 / map.width);
+                to_spawn.push((summon.monster.clone(), x, y));
+                if let (Some(name), true) = (names.get(entity), map.in_player_view(pos.x, pos.y)) {
+                    log.entries.push(format!(
+                        "{} summons {}!",
+                        &name.name,
+                        with_article(&summon.monster)
+                    ));
+                }
+            }
+        }
+        wants_summon.clear();
+    }
+    //summoned help arrives knowing exactly what it's there for
+    for (name, x, y) in to_spawn {
+        if let Some(summoned) = spawn_named_monster(ecs, &name, x, y) {
+            ecs.write_storage::<Unaware>().remove(summoned);
+        }
+    }
+}
+
+fn item_table() -> RandomTable {
+    RandomTable::new()
+        .add("Health Potion", 1)
+        .add("Magic Missile Scroll", 1)
+        .add("Fireball Scroll", 1)
+        .add("Frost Scroll", 1)
+        .add("Confusion Scroll", 1)
+        .add("Sleep Scroll", 1)
+        .add("Hold Monster Scroll", 1)
+        .add("Poison Cloud Scroll", 1)
+        .add("Haste Potion", 1)
+        .add("Identify Scroll", 1)
+        .add("Remove Curse Scroll", 1)
+        .add("Reveal Curse Scroll", 1)
+        .add("Rations", 3)
+        .add("Dagger", 1)
+        .add("Shortbow", 1)
+        .add("Sling", 1)
+        .add("Arrows", 2)
+        .add("Sling Stones", 2)
+        .add("Shield", 1)
+        .add("Leather Armor", 1)
+        .add("Iron Helm", 1)
+        .add("Ring of Strength", 1)
+        .add("Amulet of Protection", 1)
+}
+
 fn random_item(ecs: &mut World, x: i32, y: i32) {
-    let roll: i32;
+    let roll: Option<String>;
     {
         let mut rng = ecs.write_resource::<RandomNumberGenerator>();
-        roll = rng.roll_dice(1, 2);
+        roll = item_table().roll(&mut rng);
     }
-    match roll {
-        1 => health_potion(ecs, x, y),
-        _ => magic_missile_scroll(ecs, x, y),
+    if let Some(name) = roll {
+        let item = spawn_named_item(ecs, &name, x, y);
+        let cursed = ecs.read_storage::<Equippable>().get(item).is_some()
+            && ecs
+                .write_resource::<RandomNumberGenerator>()
+                .roll_dice(1, 6)
+                == 1;
+        if cursed {
+            curse(ecs, item);
+        }
     }
 }
 
-fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharType, name: S) {
+//cursed gear works against its wearer
+fn curse(ecs: &mut World, item: Entity) {
+    if let Some(weapon) = ecs.write_storage::<MeleeWeapon>().get_mut(item) {
+        weapon.hit_bonus -= 2;
+        weapon.damage_bonus -= 1;
+    }
+    if let Some(weapon) = ecs.write_storage::<RangedWeapon>().get_mut(item) {
+        weapon.hit_bonus -= 2;
+        weapon.damage_bonus -= 1;
+    }
+    if let Some(bonus) = ecs.write_storage::<DefenseBonus>().get_mut(item) {
+        bonus.defense = -bonus.defense;
+    }
+    if let Some(bonus) = ecs.write_storage::<MeleePowerBonus>().get_mut(item) {
+        bonus.power = -bonus.power;
+    }
+    ecs.write_storage::<Cursed>()
+        .insert(item, Cursed { revealed: false })
+        .expect("unable to insert curse");
+}
+
+pub fn spawn_named_item(ecs: &mut World, name: &str, x: i32, y: i32) -> Entity {
+    let item = match name {
+        "Health Potion" => health_potion(ecs, x, y),
+        "Magic Missile Scroll" => magic_missile_scroll(ecs, x, y),
+        "Fireball Scroll" => fireball_scroll(ecs, x, y),
+        "Frost Scroll" => frost_scroll(ecs, x, y),
+        "Confusion Scroll" => {
+            status_scroll(ecs, x, y, "Confusion Scroll", StatusEffect::Confusion, 4)
+        }
+        "Sleep Scroll" => status_scroll(ecs, x, y, "Sleep Scroll", StatusEffect::Sleep, 8),
+        "Hold Monster Scroll" => {
+            status_scroll(ecs, x, y, "Hold Monster Scroll", StatusEffect::Paralysis, 3)
+        }
+        "Poison Cloud Scroll" => status_scroll(
+            ecs,
+            x,
+            y,
+            "Poison Cloud Scroll",
+            StatusEffect::Poison { damage: 2 },
+            5,
+        ),
+        "Haste Potion" => haste_potion(ecs, x, y),
+        "Identify Scroll" => identify_scroll(ecs, x, y),
+        "Remove Curse Scroll" => remove_curse_scroll(ecs, x, y),
+        "Reveal Curse Scroll" => reveal_curse_scroll(ecs, x, y),
+        "Rations" => rations(ecs, x, y),
+        "Dagger" => dagger(ecs, x, y),
+        "Shortbow" => shortbow(ecs, x, y),
+        "Sling" => sling(ecs, x, y),
+        "Arrow" => ammunition(ecs, x, y, "Arrow", AmmoType::Arrow),
+        "Sling Stone" => ammunition(ecs, x, y, "Sling Stone", AmmoType::SlingStone),
+        "Arrows" => bundle(ecs, x, y, "Arrow"),
+        "Sling Stones" => bundle(ecs, x, y, "Sling Stone"),
+        "Shield" => shield(ecs, x, y),
+        "Leather Armor" => leather_armor(ecs, x, y),
+        "Iron Helm" => iron_helm(ecs, x, y),
+        "Ring of Strength" => ring_of_strength(ecs, x, y),
+        "Amulet of Protection" => amulet_of_protection(ecs, x, y),
+        _ => panic!("no item named {}", name),
+    };
+    //potions and scrolls start out known only by their look
+    if let Some(magic) = ecs.read_storage::<MagicItem>().get(item) {
+        let mut rng = ecs.write_resource::<RandomNumberGenerator>();
+        ecs.write_resource::<Identification>()
+            .disguise(name, magic.class, &mut rng);
+    }
+    item
+}
+
+//a handful of the named item on one tile
+fn bundle(ecs: &mut World, x: i32, y: i32, name: &str) -> Entity {
+    let count = ecs
+        .write_resource::<RandomNumberGenerator>()
+        .roll_dice(1, 4)
+        + 2;
+    let item = spawn_named_item(ecs, name, x, y);
+    set_quantity(ecs, item, count);
+    item
+}
+
+fn set_quantity(ecs: &mut World, item: Entity, quantity: i32) {
+    if let Some(stack) = ecs.write_storage::<Stackable>().get_mut(item) {
+        stack.quantity = quantity;
+    }
+}
+
+//takes some of a carried stack off into a new stack of its own, still in the same backpack
+pub fn split_stack(ecs: &mut World, item: Entity, quantity: i32) -> Option<Entity> {
+    let name = ecs.read_storage::<Named>().get(item)?.name.clone();
+    let owner = ecs.read_storage::<InBackpack>().get(item)?.owner;
+    {
+        let mut stacks = ecs.write_storage::<Stackable>();
+        let stack = stacks.get_mut(item)?;
+        if quantity < 1 || quantity >= stack.quantity {
+            return None;
+        }
+        stack.quantity -= quantity;
+    }
+    let split = spawn_named_item(ecs, &name, 0, 0);
+    set_quantity(ecs, split, quantity);
+    give_item(ecs, owner, split);
+    Some(split)
+}
+
+fn give_faction(ecs: &mut World, entity: Entity, faction: FactionId) {
+    ecs.write_storage::<Faction>()
+        .insert(entity, Faction { id: faction })
+        .expect("unable to insert faction");
+}
+
+fn ability(kind: AbilityKind, range: i32, chance: i32, cooldown: i32) -> MonsterAbility {
+    MonsterAbility {
+        kind,
+        range,
+        chance,
+        cooldown,
+        cooldown_remaining: 0,
+    }
+}
+
+fn give_abilities(ecs: &mut World, entity: Entity, abilities: Vec<MonsterAbility>) {
+    ecs.write_storage::<Abilities>()
+        .insert(entity, Abilities { abilities })
+        .expect("unable to insert abilities");
+}
+
+fn give_loot(ecs: &mut World, entity: Entity, drop_chance: i32, table: RandomTable) {
+    ecs.write_storage::<LootTable>()
+        .insert(entity, LootTable { drop_chance, table })
+        .expect("unable to insert loot table");
+}
+
+fn natural_attack(
+    name: &str,
+    n_dice: i32,
+    die_type: i32,
+    bonus: i32,
+    damage_type: DamageType,
+) -> NaturalAttack {
+    NaturalAttack {
+        name: name.to_string(),
+        hit_bonus: 0,
+        damage_n_dice: n_dice,
+        damage_die_type: die_type,
+        damage_bonus: bonus,
+        damage_type,
+    }
+}
+
+fn monster<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    name: S,
+    attacks: Vec<NaturalAttack>,
+) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -76,36 +370,442 @@ fn monster<S: ToString>(ecs: &mut World, x: i32, y: i32, glyph: rltk::FontCharTy
             dirty: true,
         })
         .with(Monster {})
+        .with(MonsterMemory {
+            state: AiState::Idle,
+            last_seen: None,
+            search_turns: 0,
+        })
+        .with(Unaware {})
+        .with(Morale { value: 10, max: 10 })
         .with(Named {
             name: name.to_string(),
         })
         .with(BlocksTile {})
-        .with(CombatStats {
-            max_hp: 16,
-            hp: 16,
-            defense: 1,
-            power: 4,
+        .with(CombatStats { max_hp: 16, hp: 16 })
+        .with(Attributes::new(10, 10, 10, 10))
+        .with(NaturalAttackDefense {
+            armor_class: None,
+            attacks,
         })
-        .build();
+        .build()
+}
+
+fn orc(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let orc = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('o'),
+        "Orc",
+        vec![natural_attack("claws", 1, 6, 0, DamageType::Slashing)],
+    );
+    ecs.write_storage::<Attributes>()
+        .insert(orc, Attributes::new(13, 12, 9, 8))
+        .expect("unable to insert orc attributes");
+    give_faction(ecs, orc, FactionId::Orcs);
+    give_loot(
+        ecs,
+        orc,
+        30,
+        RandomTable::new()
+            .add("Health Potion", 3)
+            .add("Magic Missile Scroll", 1),
+    );
+    orc
+}
+
+fn goblin(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let goblin = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('g'),
+        "Goblin",
+        vec![
+            natural_attack("bite", 1, 4, 0, DamageType::Piercing),
+            natural_attack("claws", 1, 3, 0, DamageType::Slashing),
+        ],
+    );
+    ecs.write_storage::<Attributes>()
+        .insert(goblin, Attributes::new(9, 10, 13, 9))
+        .expect("unable to insert goblin attributes");
+    give_faction(ecs, goblin, FactionId::Goblins);
+    give_loot(
+        ecs,
+        goblin,
+        20,
+        RandomTable::new()
+            .add("Health Potion", 1)
+            .add("Magic Missile Scroll", 1),
+    );
+    goblin
+}
+
+fn fire_elemental(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let elemental = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('E'),
+        "Fire Elemental",
+        vec![natural_attack("burning touch", 1, 6, 0, DamageType::Fire)],
+    );
+    ecs.write_storage::<Attributes>()
+        .insert(elemental, Attributes::new(11, 10, 12, 6))
+        .expect("unable to insert elemental attributes");
+    give_faction(ecs, elemental, FactionId::Elementals);
+    ecs.write_storage::<Immunities>()
+        .insert(
+            elemental,
+            Immunities {
+                damage_types: vec![DamageType::Fire, DamageType::Poison],
+            },
+        )
+        .expect("unable to insert elemental immunities");
+    ecs.write_storage::<Resistances>()
+        .insert(
+            elemental,
+            Resistances {
+                damage_types: vec![DamageType::Slashing, DamageType::Piercing],
+            },
+        )
+        .expect("unable to insert elemental resistances");
+    ecs.write_storage::<Vulnerabilities>()
+        .insert(
+            elemental,
+            Vulnerabilities {
+                damage_types: vec![DamageType::Cold],
+            },
+        )
+        .expect("unable to insert elemental vulnerabilities");
+    elemental
+}
+
+//the player's starting companion
+pub fn dog(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let dog = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('d'),
+        "Dog",
+        vec![natural_attack("bite", 1, 6, 0, DamageType::Piercing)],
+    );
+    ecs.write_storage::<Renderable>()
+        .insert(
+            dog,
+            Renderable {
+                glyph: rltk::to_cp437('d'),
+                fg: RGB::named(rltk::CYAN),
+                bg: RGB::named(rltk::BLACK),
+                render_order: 1,
+            },
+        )
+        .expect("unable to insert dog renderable");
+    ecs.write_storage::<Attributes>()
+        .insert(dog, Attributes::new(11, 12, 14, 4))
+        .expect("unable to insert dog attributes");
+    ecs.write_storage::<Ally>()
+        .insert(dog, Ally {})
+        .expect("unable to insert ally");
+    ecs.write_storage::<Unaware>().remove(dog);
+    //loyal to the end, so it never breaks and runs or surrenders
+    ecs.write_storage::<Morale>().remove(dog);
+    give_faction(ecs, dog, FactionId::Player);
+    dog
+}
+
+fn goblin_archer(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let archer = goblin(ecs, x, y);
+    ecs.write_storage::<Named>()
+        .insert(
+            archer,
+            Named {
+                name: "Goblin Archer".to_string(),
+            },
+        )
+        .expect("unable to insert archer name");
+    let bow = shortbow(ecs, x, y);
+    give_item(ecs, archer, bow);
+    equip_item(ecs, archer, bow, EquipmentSlot::Ranged);
+    let arrows = ammunition(ecs, x, y, "Arrow", AmmoType::Arrow);
+    set_quantity(ecs, arrows, 8);
+    give_item(ecs, archer, arrows);
+    give_abilities(ecs, archer, vec![ability(AbilityKind::Shoot, 8, 60, 0)]);
+    archer
+}
+
+fn orc_shaman(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let shaman = orc(ecs, x, y);
+    ecs.write_storage::<Named>()
+        .insert(
+            shaman,
+            Named {
+                name: "Orc Shaman".to_string(),
+            },
+        )
+        .expect("unable to insert shaman name");
+    ecs.write_storage::<Attributes>()
+        .insert(shaman, Attributes::new(10, 10, 10, 14))
+        .expect("unable to insert shaman attributes");
+    give_abilities(
+        ecs,
+        shaman,
+        vec![
+            ability(
+                AbilityKind::Cast(Spell {
+                    name: "Fire Bolt".to_string(),
+                    damage: 6,
+                    damage_type: DamageType::Fire,
+                }),
+                6,
+                50,
+                3,
+            ),
+            ability(AbilityKind::Summon("Orc".to_string()), 8, 20, 15),
+        ],
+    );
+    shaman
+}
+
+fn peasant(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let peasant = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('p'),
+        "Peasant",
+        vec![natural_attack("pitchfork", 1, 4, 0, DamageType::Piercing)],
+    );
+    ecs.write_storage::<Renderable>()
+        .insert(
+            peasant,
+            Renderable {
+                glyph: rltk::to_cp437('p'),
+                fg: RGB::named(rltk::GREEN),
+                bg: RGB::named(rltk::BLACK),
+                render_order: 1,
+            },
+        )
+        .expect("unable to insert peasant renderable");
+    ecs.write_storage::<CombatStats>()
+        .insert(peasant, CombatStats { max_hp: 8, hp: 8 })
+        .expect("unable to insert peasant stats");
+    give_faction(ecs, peasant, FactionId::Townsfolk);
+    peasant
+}
+
+fn goblin_shaman(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let shaman = monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('G'),
+        "Goblin Shaman",
+        vec![natural_attack("staff", 1, 4, 1, DamageType::Bludgeoning)],
+    );
+    ecs.write_storage::<CombatStats>()
+        .insert(shaman, CombatStats { max_hp: 12, hp: 12 })
+        .expect("unable to insert shaman stats");
+    ecs.write_storage::<Attributes>()
+        .insert(shaman, Attributes::new(8, 9, 12, 14))
+        .expect("unable to insert shaman attributes");
+    give_abilities(
+        ecs,
+        shaman,
+        vec![ability(
+            AbilityKind::Cast(Spell {
+                name: "Lightning".to_string(),
+                damage: 4,
+                damage_type: DamageType::Force,
+            }),
+            5,
+            40,
+            4,
+        )],
+    );
+    give_faction(ecs, shaman, FactionId::Goblins);
+    give_loot(
+        ecs,
+        shaman,
+        50,
+        RandomTable::new()
+            .add("Health Potion", 1)
+            .add("Magic Missile Scroll", 3),
+    );
+    shaman
+}
+
+//first spawn point gets the leader, the rest get followers sharing its group id
+fn goblin_war_band(ecs: &mut World, spawn_points: &[usize]) {
+    let group_id;
+    {
+        let mut group_ids = ecs.write_resource::<GroupIds>();
+        group_id = group_ids.next;
+        group_ids.next += 1;
+    }
+
+    let mut members: Vec<Entity> = Vec::new();
+    for (i, idx) in spawn_points.iter().enumerate() {
+        let x = (*idx % MAP_WIDTH) as i32;
+        let y = (*idx / MAP_WIDTH) as i32;
+        if i == 0 {
+            let leader = goblin_shaman(ecs, x, y);
+            ecs.write_storage::<GroupLeader>()
+                .insert(leader, GroupLeader {})
+                .expect("unable to insert group leader");
+            members.push(leader);
+        } else {
+            members.push(goblin(ecs, x, y));
+        }
+    }
+
+    let mut group_members = ecs.write_storage::<GroupMember>();
+    for member in members {
+        group_members
+            .insert(member, GroupMember { group_id })
+            .expect("unable to insert group member");
+    }
+}
+
+//each unique appears at most once per game, in the room farthest from the start
+pub fn spawn_uniques(ecs: &mut World, map: &Map) {
+    if map.rooms.len() < 2 {
+        return;
+    }
+    let (start_x, start_y) = map.rooms[0].center();
+    let start = rltk::Point::new(start_x, start_y);
+    let distance = |room: &Rect| {
+        let (x, y) = room.center();
+        rltk::DistanceAlg::Pythagoras.distance2d(start, rltk::Point::new(x, y))
+    };
+    let mut farthest = &map.rooms[1];
+    for room in map.rooms.iter().skip(1) {
+        if distance(room) > distance(farthest) {
+            farthest = room;
+        }
+    }
+
+    for (name, depth, spawn) in UNIQUES.iter() {
+        if *depth != map.depth || ecs.fetch::<UniqueRegistry>().spawned.contains(*name) {
+            continue;
+        }
+        //only counts as spawned once it's actually been placed
+        if let Some((x, y)) = free_tile(ecs, farthest) {
+            spawn(ecs, x, y);
+            ecs.write_resource::<UniqueRegistry>()
+                .spawned
+                .insert(name.to_string());
+        }
+    }
+}
+
+//the room's centre if nothing stands there, otherwise the first open spot inside it
+fn free_tile(ecs: &World, room: &Rect) -> Option<(i32, i32)> {
+    let positions = ecs.read_storage::<Position>();
+    let blockers = ecs.read_storage::<BlocksTile>();
+    let occupied: HashSet<(i32, i32)> = (&positions, &blockers)
+        .join()
+        .map(|(pos, _blocks)| (pos.x, pos.y))
+        .collect();
+    let centre = room.center();
+    std::iter::once(centre)
+        .chain((room.y1 + 1..=room.y2).flat_map(|y| (room.x1 + 1..=room.x2).map(move |x| (x, y))))
+        .find(|tile| !occupied.contains(tile))
 }
 
-fn orc(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('o'), "Orc");
+fn unique_monster(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    name: &str,
+    stats: CombatStats,
+    natural: NaturalAttackDefense,
+) -> Entity {
+    let boss = monster(ecs, x, y, glyph, name, Vec::new());
+    ecs.write_storage::<CombatStats>()
+        .insert(boss, stats)
+        .expect("unable to insert unique stats");
+    ecs.write_storage::<NaturalAttackDefense>()
+        .insert(boss, natural)
+        .expect("unable to insert unique attacks");
+    ecs.write_storage::<Unique>()
+        .insert(boss, Unique {})
+        .expect("unable to insert unique");
+    ecs.write_storage::<Morale>()
+        .insert(boss, Morale { value: 15, max: 15 })
+        .expect("unable to insert unique morale");
+    ecs.write_storage::<Renderable>()
+        .insert(
+            boss,
+            Renderable {
+                glyph,
+                fg: RGB::named(rltk::ORANGE),
+                bg: RGB::named(rltk::BLACK),
+                render_order: 1,
+            },
+        )
+        .expect("unable to insert unique renderable");
+    boss
 }
 
-fn goblin(ecs: &mut World, x: i32, y: i32) {
-    monster(ecs, x, y, rltk::to_cp437('g'), "Goblin");
+fn give_item(ecs: &mut World, owner: Entity, item: Entity) {
+    ecs.write_storage::<Position>().remove(item);
+    ecs.write_storage::<InBackpack>()
+        .insert(item, InBackpack { owner })
+        .expect("unable to insert backpack entry");
+}
+
+fn equip_item(ecs: &mut World, owner: Entity, item: Entity, slot: EquipmentSlot) {
+    ecs.write_storage::<InBackpack>().remove(item);
+    ecs.write_storage::<Equipped>()
+        .insert(item, Equipped { owner, slot })
+        .expect("unable to insert equipped item");
+}
+
+fn grishnak(ecs: &mut World, x: i32, y: i32) {
+    let boss = unique_monster(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('O'),
+        "Grishnak the Orc Warlord",
+        CombatStats { max_hp: 40, hp: 40 },
+        NaturalAttackDefense {
+            armor_class: Some(14),
+            attacks: vec![natural_attack("greataxe", 2, 6, 2, DamageType::Slashing)],
+        },
+    );
+    ecs.write_storage::<Attributes>()
+        .insert(boss, Attributes::new(16, 14, 10, 10))
+        .expect("unable to insert unique attributes");
+    give_faction(ecs, boss, FactionId::Orcs);
+    for _ in 0..2 {
+        let potion = spawn_named_item(ecs, "Health Potion", x, y);
+        give_item(ecs, boss, potion);
+    }
+    give_loot(
+        ecs,
+        boss,
+        100,
+        RandomTable::new().add("Magic Missile Scroll", 1),
+    );
 }
 
 pub fn spawn_room(ecs: &mut World, room: &Rect) {
     let mut monster_spawn_points: Vec<usize> = Vec::new();
     let mut item_spawn_points: Vec<usize> = Vec::new();
+    let mut group_size: usize = 0;
     {
         let mut rng = ecs.write_resource::<RandomNumberGenerator>();
         let num_monsters = rng.roll_dice(1, MAX_MONSTERS + 2) - 3;
         let num_items = rng.roll_dice(1, MAX_ITEMS + 2) - 3;
+        if rng.roll_dice(1, 5) == 1 {
+            group_size = rng.range(2, MAX_GROUP_SIZE + 1) as usize;
+        }
 
-        for _i in 0..num_monsters {
+        for _i in 0..i32::max(0, num_monsters) + group_size as i32 {
             let mut added = false;
             while !added {
                 let x = (room.x1 + rng.roll_dice(1, i32::abs(room.x2 - room.x1))) as usize;
@@ -131,7 +831,10 @@ pub fn spawn_room(ecs: &mut World, room: &Rect) {
         }
     }
 
-    for idx in monster_spawn_points.iter() {
+    if group_size > 0 {
+        goblin_war_band(ecs, &monster_spawn_points[..group_size]);
+    }
+    for idx in monster_spawn_points.iter().skip(group_size) {
         let x = *idx % MAP_WIDTH;
         let y = *idx / MAP_WIDTH;
         random_monster(ecs, x as i32, y as i32);
@@ -144,7 +847,7 @@ pub fn spawn_room(ecs: &mut World, room: &Rect) {
     }
 }
 
-fn health_potion(ecs: &mut World, x: i32, y: i32) {
+fn health_potion(ecs: &mut World, x: i32, y: i32) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
@@ -156,27 +859,483 @@ fn health_potion(ecs: &mut World, x: i32, y: i32) {
         .with(Named {
             name: "Health Potion".to_string(),
         })
-        .with(Item {})
+        .with(Item {
+            weight: 0.5,
+            value: 50,
+        })
         .with(Consumable {})
+        .with(MagicItem {
+            class: MagicItemClass::Potion,
+        })
+        .with(Stackable { quantity: 1 })
         .with(ProvidesHealing { heal_amount: 8 })
-        .build();
+        .build()
+}
+
+fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    damage_scroll(
+        ecs,
+        x,
+        y,
+        RGB::named(rltk::CYAN),
+        "Magic Missile Scroll",
+        8,
+        DamageType::Force,
+    )
+}
+
+fn damage_scroll<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    fg: RGB,
+    name: S,
+    damage: i32,
+    damage_type: DamageType,
+) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: name.to_string(),
+        })
+        .with(Item {
+            weight: 0.5,
+            value: damage * 5,
+        })
+        .with(Consumable {})
+        .with(MagicItem {
+            class: MagicItemClass::Scroll,
+        })
+        .with(Stackable { quantity: 1 })
+        .with(Ranged { range: 6 })
+        .with(InflictsDamage {
+            damage,
+            damage_type,
+        })
+        .build()
 }
 
-fn magic_missile_scroll(ecs: &mut World, x: i32, y: i32) {
+fn status_scroll(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    name: &str,
+    effect: StatusEffect,
+    turns: i32,
+) -> Entity {
     ecs.create_entity()
         .with(Position { x, y })
         .with(Renderable {
             glyph: rltk::to_cp437(')'),
-            fg: RGB::named(rltk::CYAN),
+            fg: RGB::named(rltk::PINK),
             bg: RGB::named(rltk::BLACK),
             render_order: 2,
         })
         .with(Named {
-            name: "Magic Missile Scroll".to_string(),
+            name: name.to_string(),
+        })
+        .with(Item {
+            weight: 0.5,
+            value: 30,
         })
-        .with(Item {})
         .with(Consumable {})
+        .with(MagicItem {
+            class: MagicItemClass::Scroll,
+        })
+        .with(Stackable { quantity: 1 })
         .with(Ranged { range: 6 })
-        .with(InflictsDamage { damage: 8 })
-        .build();
+        .with(AppliesStatus { effect, turns })
+        .build()
+}
+
+fn haste_potion(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('¡'),
+            fg: RGB::named(rltk::YELLOW),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: "Haste Potion".to_string(),
+        })
+        .with(Item {
+            weight: 0.5,
+            value: 50,
+        })
+        .with(Consumable {})
+        .with(MagicItem {
+            class: MagicItemClass::Potion,
+        })
+        .with(Stackable { quantity: 1 })
+        .with(AppliesStatus {
+            effect: StatusEffect::Haste,
+            turns: 10,
+        })
+        .build()
+}
+
+//a scroll that works on the reader's own belongings rather than a target
+fn utility_scroll<S: ToString>(ecs: &mut World, x: i32, y: i32, name: S) -> EntityBuilder<'_> {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437(')'),
+            fg: RGB::named(rltk::WHITE),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: name.to_string(),
+        })
+        .with(Item {
+            weight: 0.5,
+            value: 20,
+        })
+        .with(Consumable {})
+        .with(MagicItem {
+            class: MagicItemClass::Scroll,
+        })
+        .with(Stackable { quantity: 1 })
+}
+
+fn identify_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    utility_scroll(ecs, x, y, "Identify Scroll")
+        .with(ProvidesIdentification {})
+        .build()
+}
+
+fn remove_curse_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    utility_scroll(ecs, x, y, "Remove Curse Scroll")
+        .with(RemovesCurse {})
+        .build()
+}
+
+fn reveal_curse_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    utility_scroll(ecs, x, y, "Reveal Curse Scroll")
+        .with(RevealsCurses {})
+        .build()
+}
+
+fn rations(ecs: &mut World, x: i32, y: i32) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('%'),
+            fg: RGB::named(rltk::GREEN),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: "Rations".to_string(),
+        })
+        .with(Item {
+            weight: 2.0,
+            value: 5,
+        })
+        .with(Consumable {})
+        .with(Stackable { quantity: 1 })
+        .with(ProvidesFood {})
+        .build()
+}
+
+fn fireball_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    let scroll = damage_scroll(
+        ecs,
+        x,
+        y,
+        RGB::named(rltk::ORANGE),
+        "Fireball Scroll",
+        10,
+        DamageType::Fire,
+    );
+    ecs.write_storage::<AreaOfEffect>()
+        .insert(scroll, AreaOfEffect { radius: 3 })
+        .expect("unable to insert area of effect");
+    scroll
+}
+
+fn frost_scroll(ecs: &mut World, x: i32, y: i32) -> Entity {
+    damage_scroll(
+        ecs,
+        x,
+        y,
+        RGB::named(rltk::LIGHT_BLUE),
+        "Frost Scroll",
+        10,
+        DamageType::Cold,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn equipment<S: ToString>(
+    ecs: &mut World,
+    x: i32,
+    y: i32,
+    glyph: rltk::FontCharType,
+    fg: RGB,
+    name: S,
+    slot: EquipmentSlot,
+    item: Item,
+) -> EntityBuilder<'_> {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph,
+            fg,
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: name.to_string(),
+        })
+        .with(item)
+        .with(Equippable { slot })
+}
+
+fn dagger(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('/'),
+        RGB::named(rltk::CYAN),
+        "Dagger",
+        EquipmentSlot::Melee,
+        Item {
+            weight: 1.0,
+            value: 10,
+        },
+    )
+    .with(MeleeWeapon {
+        hit_bonus: 1,
+        damage_n_dice: 1,
+        damage_die_type: 4,
+        damage_bonus: 0,
+        damage_type: DamageType::Piercing,
+    })
+    .build()
+}
+
+fn shortbow(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('}'),
+        RGB::named(rltk::BROWN1),
+        "Shortbow",
+        EquipmentSlot::Ranged,
+        Item {
+            weight: 2.0,
+            value: 25,
+        },
+    )
+    .with(RangedWeapon {
+        range: 8,
+        ammo: AmmoType::Arrow,
+        hit_bonus: 0,
+        damage_n_dice: 1,
+        damage_die_type: 6,
+        damage_bonus: 0,
+        damage_type: DamageType::Piercing,
+    })
+    .build()
+}
+
+fn sling(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('}'),
+        RGB::named(rltk::GREY),
+        "Sling",
+        EquipmentSlot::Ranged,
+        Item {
+            weight: 0.5,
+            value: 5,
+        },
+    )
+    .with(RangedWeapon {
+        range: 6,
+        ammo: AmmoType::SlingStone,
+        hit_bonus: 1,
+        damage_n_dice: 1,
+        damage_die_type: 4,
+        damage_bonus: 0,
+        damage_type: DamageType::Bludgeoning,
+    })
+    .build()
+}
+
+fn ammunition(ecs: &mut World, x: i32, y: i32, name: &str, ammo_type: AmmoType) -> Entity {
+    ecs.create_entity()
+        .with(Position { x, y })
+        .with(Renderable {
+            glyph: rltk::to_cp437('|'),
+            fg: RGB::named(rltk::BROWN1),
+            bg: RGB::named(rltk::BLACK),
+            render_order: 2,
+        })
+        .with(Named {
+            name: name.to_string(),
+        })
+        .with(Item {
+            weight: 1.0,
+            value: 2,
+        })
+        .with(Ammunition { ammo_type })
+        .with(Stackable { quantity: 1 })
+        .build()
+}
+
+fn shield(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('('),
+        RGB::named(rltk::CYAN),
+        "Shield",
+        EquipmentSlot::Shield,
+        Item {
+            weight: 10.0,
+            value: 20,
+        },
+    )
+    .with(DefenseBonus { defense: 1 })
+    .build()
+}
+
+fn leather_armor(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('['),
+        RGB::named(rltk::BROWN1),
+        "Leather Armor",
+        EquipmentSlot::Armor,
+        Item {
+            weight: 15.0,
+            value: 15,
+        },
+    )
+    .with(DefenseBonus { defense: 1 })
+    .build()
+}
+
+fn iron_helm(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('^'),
+        RGB::named(rltk::GREY),
+        "Iron Helm",
+        EquipmentSlot::Helm,
+        Item {
+            weight: 5.0,
+            value: 20,
+        },
+    )
+    .with(DefenseBonus { defense: 1 })
+    .build()
+}
+
+fn ring_of_strength(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('='),
+        RGB::named(rltk::GOLD),
+        "Ring of Strength",
+        EquipmentSlot::Ring,
+        Item {
+            weight: 0.1,
+            value: 100,
+        },
+    )
+    .with(MeleePowerBonus { power: 1 })
+    .build()
+}
+
+fn amulet_of_protection(ecs: &mut World, x: i32, y: i32) -> Entity {
+    equipment(
+        ecs,
+        x,
+        y,
+        rltk::to_cp437('"'),
+        RGB::named(rltk::GOLD),
+        "Amulet of Protection",
+        EquipmentSlot::Amulet,
+        Item {
+            weight: 0.5,
+            value: 100,
+        },
+    )
+    .with(DefenseBonus { defense: 1 })
+    .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //a carrier with a stack of arrows in its backpack
+    fn carried_arrows(quantity: i32) -> (World, Entity, Entity) {
+        let mut ecs = World::new();
+        ecs.register::<Position>();
+        ecs.register::<Renderable>();
+        ecs.register::<Named>();
+        ecs.register::<Item>();
+        ecs.register::<Ammunition>();
+        ecs.register::<Stackable>();
+        ecs.register::<InBackpack>();
+        ecs.register::<MagicItem>();
+        let owner = ecs.create_entity().build();
+        let arrows = spawn_named_item(&mut ecs, "Arrow", 5, 5);
+        set_quantity(&mut ecs, arrows, quantity);
+        give_item(&mut ecs, owner, arrows);
+        (ecs, owner, arrows)
+    }
+
+    #[test]
+    fn split_leaves_both_stacks_in_the_same_backpack() {
+        let (mut ecs, owner, arrows) = carried_arrows(5);
+
+        let split = split_stack(&mut ecs, arrows, 2).expect("stack should split");
+
+        let stacks = ecs.read_storage::<Stackable>();
+        let backpack = ecs.read_storage::<InBackpack>();
+        assert_eq!(stacks.get(arrows).unwrap().quantity, 3);
+        assert_eq!(stacks.get(split).unwrap().quantity, 2);
+        assert_eq!(backpack.get(split).unwrap().owner, owner);
+        assert!(ecs.read_storage::<Position>().get(split).is_none());
+    }
+
+    #[test]
+    fn split_of_the_whole_stack_or_more_is_refused() {
+        let (mut ecs, _owner, arrows) = carried_arrows(5);
+
+        assert!(split_stack(&mut ecs, arrows, 5).is_none());
+        assert!(split_stack(&mut ecs, arrows, 6).is_none());
+        assert!(split_stack(&mut ecs, arrows, 0).is_none());
+        assert_eq!(
+            ecs.read_storage::<Stackable>()
+                .get(arrows)
+                .unwrap()
+                .quantity,
+            5
+        );
+    }
 }
@@ -0,0 +1,90 @@
+use crate::particle_system::ParticleBuilder;
+use crate::scent_system::Noises;
+use crate::{CombatStats, GameLog, Map, Named, Position, SufferDamage, WantsToCastSpell};
+use rltk::{Point, RGB};
+use specs::prelude::*;
+
+pub struct SpellSystem {}
+
+impl<'a> System<'a> for SpellSystem {
+    type SystemData = (
+        Entities<'a>,
+        WriteStorage<'a, WantsToCastSpell>,
+        ReadStorage<'a, Named>,
+        ReadStorage<'a, CombatStats>,
+        ReadStorage<'a, Position>,
+        ReadExpect<'a, Map>,
+        WriteStorage<'a, SufferDamage>,
+        WriteExpect<'a, GameLog>,
+        WriteExpect<'a, ParticleBuilder>,
+        WriteExpect<'a, Noises>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut wants_cast,
+            names,
+            combat_stats,
+            positions,
+            map,
+            mut suffer_damage,
+            mut log,
+            mut particle_builder,
+            mut noises,
+        ) = data;
+
+        for (entity, cast, name, pos) in (&entities, &wants_cast, &names, &positions).join() {
+            //spells fly like missiles and burst on the first thing in the way
+            let path = map.line_of_fire(Point::new(pos.x, pos.y), cast.target);
+            noises.make(Point::new(pos.x, pos.y), 8.0);
+            for tile in path.iter() {
+                particle_builder.request(
+                    tile.x,
+                    tile.y,
+                    RGB::named(rltk::MAGENTA),
+                    RGB::named(rltk::BLACK),
+                    rltk::to_cp437('*'),
+                    100.0,
+                );
+            }
+            let victim = path.last().and_then(|impact| {
+                let idx = map.xy_idx(impact.x, impact.y);
+                map.tile_content[idx]
+                    .iter()
+                    .find(|e| **e != entity && combat_stats.get(**e).is_some())
+                    .copied()
+            });
+            let seen = map.player_sees_either_end(Point::new(pos.x, pos.y), &path);
+            match victim {
+                None => {
+                    if seen {
+                        log.entries.push(format!(
+                            "{} casts {}, but it fizzles against the wall.",
+                            &name.name, cast.spell.name
+                        ));
+                    }
+                }
+                Some(victim) => {
+                    if seen {
+                        log.entries.push(format!(
+                            "{} casts {} at {} for {} hp!",
+                            &name.name,
+                            cast.spell.name,
+                            names.get(victim).map_or("something", |n| n.name.as_str()),
+                            cast.spell.damage
+                        ));
+                    }
+                    SufferDamage::new_damage(
+                        &mut suffer_damage,
+                        victim,
+                        cast.spell.damage,
+                        cast.spell.damage_type,
+                        Some(entity),
+                    );
+                }
+            }
+        }
+        wants_cast.clear();
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    Asleep, Confused, DamageType, GameLog, Hasted, Map, Monster, Named, Paralyzed, Poisoned,
+    Position, RunState, StatusEffect, SufferDamage,
+};
+use specs::prelude::*;
+
+pub struct StatusEffectSystem {}
+
+impl<'a> System<'a> for StatusEffectSystem {
+    type SystemData = (
+        Entities<'a>,
+        ReadExpect<'a, Entity>,
+        ReadExpect<'a, RunState>,
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Named>,
+        WriteStorage<'a, SufferDamage>,
+        WriteStorage<'a, Confused>,
+        WriteStorage<'a, Poisoned>,
+        WriteStorage<'a, Asleep>,
+        WriteStorage<'a, Paralyzed>,
+        WriteStorage<'a, Hasted>,
+        ReadExpect<'a, Map>,
+        ReadStorage<'a, Position>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            player_entity,
+            runstate,
+            mut log,
+            names,
+            mut suffer_damage,
+            mut confused,
+            mut poisoned,
+            mut asleep,
+            mut paralyzed,
+            mut hasted,
+            map,
+            positions,
+        ) = data;
+
+        //effects tick down once per round
+        if *runstate != RunState::MonsterTurn {
+            return;
+        }
+
+        let mut expired: Vec<(Entity, StatusEffect)> = Vec::new();
+
+        for (entity, effect) in (&entities, &mut confused).join() {
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                expired.push((entity, StatusEffect::Confusion));
+            }
+        }
+        for (entity, effect) in (&entities, &mut poisoned).join() {
+            SufferDamage::new_damage(
+                &mut suffer_damage,
+                entity,
+                effect.damage,
+                DamageType::Poison,
+                effect.source,
+            );
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                expired.push((
+                    entity,
+                    StatusEffect::Poison {
+                        damage: effect.damage,
+                    },
+                ));
+            }
+        }
+        for (entity, effect) in (&entities, &mut asleep).join() {
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                expired.push((entity, StatusEffect::Sleep));
+            }
+        }
+        for (entity, effect) in (&entities, &mut paralyzed).join() {
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                expired.push((entity, StatusEffect::Paralysis));
+            }
+        }
+        for (entity, effect) in (&entities, &mut hasted).join() {
+            effect.turns -= 1;
+            if effect.turns < 1 {
+                expired.push((entity, StatusEffect::Haste));
+            }
+        }
+
+        for (entity, effect) in expired {
+            match effect {
+                StatusEffect::Confusion => {
+                    confused.remove(entity);
+                }
+                StatusEffect::Poison { .. } => {
+                    poisoned.remove(entity);
+                }
+                StatusEffect::Sleep => {
+                    asleep.remove(entity);
+                }
+                StatusEffect::Paralysis => {
+                    paralyzed.remove(entity);
+                }
+                StatusEffect::Haste => {
+                    hasted.remove(entity);
+                }
+            }
+            if entity == *player_entity {
+                log.entries
+                    .push(format!("You are no longer {}.", effect.adjective()));
+            } else if let (Some(name), true) =
+                (names.get(entity), map.player_sees(&positions, entity))
+            {
+                log.entries.push(format!(
+                    "{} is no longer {}.",
+                    &name.name,
+                    effect.adjective()
+                ));
+            }
+        }
+    }
+}
+
+//a hasted player gets every other action for free, before the monsters move
+pub fn player_has_free_action(ecs: &mut World) -> bool {
+    let player_entity = *ecs.fetch::<Entity>();
+    let mut hasted = ecs.write_storage::<Hasted>();
+    if let Some(haste) = hasted.get_mut(player_entity) {
+        haste.free_action = !haste.free_action;
+        return haste.free_action;
+    }
+    false
+}
+
+//hasted monsters take a second turn straight after everyone else's
+pub fn monster_has_haste(ecs: &World) -> bool {
+    let hasted = ecs.read_storage::<Hasted>();
+    let monsters = ecs.read_storage::<Monster>();
+    (&hasted, &monsters).join().next().is_some()
+}